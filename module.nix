@@ -21,15 +21,52 @@ in
       type = lib.types.enum [
         "open-meteo"
         "open-weather-map"
+        "met-no"
+        "weather-api"
+        "nws"
+        "wttr"
       ];
       default = "open-meteo";
     };
 
+    providers = lib.mkOption {
+      type = with lib.types; listOf (enum [
+        "open-meteo"
+        "open-weather-map"
+        "met-no"
+        "weather-api"
+        "nws"
+        "wttr"
+      ]);
+      default = [ ];
+      example = [
+        "open-meteo"
+        "wttr"
+      ];
+    };
+
     apiKey = lib.mkOption {
       type = with lib.types; nullOr str;
       default = null;
     };
 
+    apiKeyKeyring = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+      example = "weather-cli/api_key";
+    };
+
+    apiKeyFile = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+      example = "/run/secrets/weather-cli";
+    };
+
+    userAgent = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+    };
+
     location = lib.mkOption {
       type = with lib.types; nullOr (either (listOf str) (listOf float));
       default = null;
@@ -39,6 +76,23 @@ in
       ];
     };
 
+    locations = lib.mkOption {
+      type = with lib.types; attrsOf (either (listOf str) (listOf float));
+      default = { };
+      example = {
+        work = [
+          "Hamburg"
+          "DE"
+        ];
+      };
+    };
+
+    locationsFile = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+      example = "/etc/weather-cli/locations.csv";
+    };
+
     units = lib.mkOption {
       type = lib.types.enum [
         "metric"
@@ -47,18 +101,253 @@ in
       default = "metric";
     };
 
+    temperatureSuffixStyle = lib.mkOption {
+      type = lib.types.enum [
+        "degree_letter"
+        "degree_only"
+        "letter_only"
+        "spaced"
+      ];
+      default = "degree_letter";
+    };
+
+    temperaturePrecision = lib.mkOption {
+      type = lib.types.ints.between 0 3;
+      default = 0;
+    };
+
+    columnWidth = lib.mkOption {
+      type = with lib.types; nullOr ints.positive;
+      default = null;
+    };
+
+    feelsLike = lib.mkOption {
+      type = lib.types.enum [
+        "provider"
+        "computed"
+      ];
+      default = "provider";
+    };
+
     timeFormat = lib.mkOption {
       type = lib.types.enum [
         "12h"
         "24h"
+        "auto"
       ];
       default = "24h";
     };
 
+    timeFormatString = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+    };
+
+    timezone = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+      example = "Europe/Berlin";
+    };
+
     cachingDuration = lib.mkOption {
-      type = lib.types.strMatching "^[0-9]+(min|h)$";
+      type = lib.types.strMatching "^([0-9]+(d|h|min|s))+$";
       default = "1h";
     };
+
+    dailyCachingDuration = lib.mkOption {
+      type = lib.types.strMatching "^([0-9]+(d|h|min|s))+$";
+      default = "12h";
+    };
+
+    cachePath = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+    };
+
+    locationSource = lib.mkOption {
+      type = lib.types.enum [
+        "ip"
+        "gpsd"
+      ];
+      default = "ip";
+    };
+
+    allowIpGeolocation = lib.mkOption {
+      type = lib.types.bool;
+      default = true;
+    };
+
+    locationCacheDuration = lib.mkOption {
+      type = lib.types.strMatching "^([0-9]+(d|h|min|s))+$";
+      default = "6h";
+    };
+
+    requestTimeout = lib.mkOption {
+      type = lib.types.strMatching "^([0-9]+(d|h|min|s))+$";
+      default = "10s";
+    };
+
+    maxRetries = lib.mkOption {
+      type = lib.types.ints.unsigned;
+      default = 2;
+    };
+
+    retryDelay = lib.mkOption {
+      type = lib.types.strMatching "^([0-9]+(d|h|min|s))+$";
+      default = "1s";
+    };
+
+    cacheMode = lib.mkOption {
+      type = lib.types.enum [
+        "ttl"
+        "swr"
+      ];
+      default = "ttl";
+    };
+
+    staleWhileRevalidate = lib.mkOption {
+      type = with lib.types; nullOr (strMatching "^([0-9]+(d|h|min|s))+$");
+      default = null;
+    };
+
+    showIcons = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    showAirQuality = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    showAlerts = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    showMoon = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    showDewPoint = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    showCloudCover = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+    };
+
+    fields = lib.mkOption {
+      type = with lib.types; nullOr (listOf (enum [
+        "temperature"
+        "feels_like"
+        "condition"
+        "wind"
+        "humidity"
+        "dew_point"
+        "pressure"
+        "visibility"
+        "cloud_cover"
+        "uv_index"
+        "precipitation"
+        "snow"
+        "sun"
+        "moon"
+        "alerts"
+        "location"
+        "air_quality"
+        "time"
+        "provider"
+      ]));
+      default = null;
+      example = [
+        "temperature"
+        "condition"
+        "wind"
+        "time"
+      ];
+    };
+
+    format = lib.mkOption {
+      type = with lib.types; nullOr str;
+      default = null;
+      example = "{temp} {condition} {wind}";
+    };
+
+    color = lib.mkOption {
+      type = lib.types.enum [
+        "always"
+        "auto"
+        "never"
+      ];
+      default = "auto";
+    };
+
+    pressureUnit = lib.mkOption {
+      type = lib.types.enum [
+        "hPa"
+        "inHg"
+        "mmHg"
+        "kPa"
+      ];
+      default = "hPa";
+    };
+
+    temperatureUnit = lib.mkOption {
+      type = with lib.types; nullOr (enum [
+        "celsius"
+        "fahrenheit"
+        "kelvin"
+      ]);
+      default = null;
+    };
+
+    windUnit = lib.mkOption {
+      type = with lib.types; nullOr (enum [
+        "kmh"
+        "mph"
+        "ms"
+        "knots"
+        "beaufort"
+      ]);
+      default = null;
+    };
+
+    windDirectionPoints = lib.mkOption {
+      type = lib.types.enum [
+        4
+        8
+        16
+      ];
+      default = 16;
+    };
+
+    windArrow = lib.mkOption {
+      type = with lib.types; nullOr (enum [
+        "from"
+        "to"
+      ]);
+      default = null;
+    };
+
+    temperatureIconThresholds = lib.mkOption {
+      type =
+        with lib.types;
+        nullOr (submodule {
+          options = {
+            cold = lib.mkOption { type = number; };
+            hot = lib.mkOption { type = number; };
+          };
+        });
+      default = null;
+      example = {
+        cold = 5;
+        hot = 25;
+      };
+    };
   };
 
   config = lib.mkIf cfg.enable {
@@ -67,10 +356,47 @@ in
 
     xdg.configFile."weather-cli.toml".text =
       ''provider = "${cfg.provider}"
+${if (cfg.providers != [ ]) then "providers = [${lib.concatStringsSep ", " (map (provider: "\"${provider}\"") cfg.providers)}]" else ""}
 ${if (cfg.apiKey != null) then "api_key = \"${cfg.apiKey}\"" else ""}
+${if (cfg.apiKeyKeyring != null) then "api_key_keyring = \"${cfg.apiKeyKeyring}\"" else ""}
+${if (cfg.apiKeyFile != null) then "api_key_file = \"${cfg.apiKeyFile}\"" else ""}
+${if (cfg.userAgent != null) then "user_agent = \"${cfg.userAgent}\"" else ""}
 ${if (cfg.location != null) then "location = [${lib.concatStringsSep ", " (map (loc: if builtins.isString loc then "\"${loc}\"" else toString loc) cfg.location)}]" else "" }
+${if (cfg.locationsFile != null) then "locations_file = \"${cfg.locationsFile}\"" else ""}
 units = "${cfg.units}"
+temperature_suffix_style = "${cfg.temperatureSuffixStyle}"
+temperature_precision = ${toString cfg.temperaturePrecision}
+feels_like = "${cfg.feelsLike}"
 time_format = "${cfg.timeFormat}"
-caching_duration = "${cfg.cachingDuration}"'';
+caching_duration = "${cfg.cachingDuration}"
+daily_caching_duration = "${cfg.dailyCachingDuration}"
+cache_mode = "${cfg.cacheMode}"
+request_timeout = "${cfg.requestTimeout}"
+max_retries = ${toString cfg.maxRetries}
+retry_delay = "${cfg.retryDelay}"
+location_source = "${cfg.locationSource}"
+allow_ip_geolocation = ${lib.boolToString cfg.allowIpGeolocation}
+location_cache_duration = "${cfg.locationCacheDuration}"
+pressure_unit = "${cfg.pressureUnit}"
+color = "${cfg.color}"
+show_icons = ${lib.boolToString cfg.showIcons}
+show_air_quality = ${lib.boolToString cfg.showAirQuality}
+show_moon = ${lib.boolToString cfg.showMoon}
+show_dew_point = ${lib.boolToString cfg.showDewPoint}
+show_cloud_cover = ${lib.boolToString cfg.showCloudCover}
+show_alerts = ${lib.boolToString cfg.showAlerts}
+wind_direction_points = ${toString cfg.windDirectionPoints}
+${if (cfg.staleWhileRevalidate != null) then "stale_while_revalidate = \"${cfg.staleWhileRevalidate}\"" else ""}
+${if (cfg.cachePath != null) then "cache_path = \"${cfg.cachePath}\"" else ""}
+${if (cfg.fields != null) then "fields = [${lib.concatStringsSep ", " (map (field: "\"${field}\"") cfg.fields)}]" else ""}
+${if (cfg.format != null) then "format = \"${cfg.format}\"" else ""}
+${if (cfg.timeFormatString != null) then "time_format_string = \"${cfg.timeFormatString}\"" else ""}
+${if (cfg.columnWidth != null) then "column_width = ${toString cfg.columnWidth}" else ""}
+${if (cfg.timezone != null) then "timezone = \"${cfg.timezone}\"" else ""}
+${if (cfg.temperatureUnit != null) then "temperature_unit = \"${cfg.temperatureUnit}\"" else ""}
+${if (cfg.windUnit != null) then "wind_unit = \"${cfg.windUnit}\"" else ""}
+${if (cfg.windArrow != null) then "wind_arrow = \"${cfg.windArrow}\"" else ""}
+${if (cfg.temperatureIconThresholds != null) then "temperature_icon_thresholds = { cold = ${toString cfg.temperatureIconThresholds.cold}, hot = ${toString cfg.temperatureIconThresholds.hot} }" else ""}
+${lib.concatStrings (lib.mapAttrsToList (name: loc: "\n[locations.${name}]\nlocation = [${lib.concatStringsSep ", " (map (l: if builtins.isString l then "\"${l}\"" else toString l) loc)}]\n") cfg.locations)}'';
   };
 }
@@ -0,0 +1,92 @@
+//! Simple `field op value` comparisons such as `temp<0` or `wind>=40`,
+//! evaluated against the current reading.
+//!
+//! Values are compared in the units the reading was fetched in.
+
+use crate::{WeatherData, numeric_value};
+use std::str::FromStr;
+
+const FIELDS: &str = "temp, feels, wind, humidity";
+
+#[derive(Clone, Copy)]
+enum Field {
+    Temperature,
+    FeelsLike,
+    WindSpeed,
+    Humidity,
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone)]
+pub struct Condition {
+    field: Field,
+    operator: Operator,
+    value: f32,
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Two-character operators first, so `<=` isn't read as `<` and `=0`.
+        let (position, operator, length) = [
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ]
+        .into_iter()
+        .find_map(|(symbol, operator)| s.find(symbol).map(|i| (i, operator, symbol.len())))
+        .ok_or_else(|| format!("expected <field><op><value>, e.g. temp<0, got '{}'", s))?;
+
+        let field = match s[..position].trim() {
+            "temp" | "temperature" => Field::Temperature,
+            "feels" | "feels_like" => Field::FeelsLike,
+            "wind" | "wind_speed" => Field::WindSpeed,
+            "humidity" => Field::Humidity,
+            field => {
+                return Err(format!(
+                    "unknown field '{}', expected one of {}",
+                    field, FIELDS
+                ));
+            }
+        };
+        let value = s[position + length..]
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", s[position + length..].trim()))?;
+
+        Ok(Condition {
+            field,
+            operator,
+            value,
+        })
+    }
+}
+
+impl Condition {
+    /// Whether the reading satisfies the condition. Values that can't be read
+    /// as a number never match.
+    pub fn matches(&self, weather: &WeatherData) -> bool {
+        let current = match self.field {
+            Field::Temperature => numeric_value(&weather.temperature),
+            Field::FeelsLike => numeric_value(&weather.feels_like),
+            Field::WindSpeed => numeric_value(&weather.wind_speed),
+            Field::Humidity => weather.humidity.map(f32::from),
+        };
+
+        current.is_some_and(|current| match self.operator {
+            Operator::Less => current < self.value,
+            Operator::LessOrEqual => current <= self.value,
+            Operator::Greater => current > self.value,
+            Operator::GreaterOrEqual => current >= self.value,
+        })
+    }
+}
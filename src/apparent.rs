@@ -0,0 +1,129 @@
+//! Feels-like temperatures computed from the reading itself, so they mean the
+//! same whichever provider it came from. Everything is in °C and km/h.
+
+/// The heat index at a relative humidity in percent, following the NWS: the
+/// Rothfusz regression with its adjustments for very dry and very humid air,
+/// or Steadman's simpler formula where that gives less than 80°F.
+pub fn heat_index(celsius: f32, humidity: f32) -> f32 {
+    let t = celsius * 9.0 / 5.0 + 32.0;
+    let rh = humidity;
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+
+    if (simple + t) / 2.0 < 80.0 {
+        return fahrenheit_to_celsius(simple);
+    }
+
+    let mut index = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+        - 0.224_755_4 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+
+    fahrenheit_to_celsius(index)
+}
+
+/// The wind chill, using the 2001 formula of the US and Canadian weather
+/// services.
+pub fn wind_chill(celsius: f32, kmh: f32) -> f32 {
+    let wind = kmh.powf(0.16);
+
+    13.12 + 0.6215 * celsius - 11.37 * wind + 0.3965 * celsius * wind
+}
+
+/// The wind chill at or below 10°C with more than 4.8km/h of wind, the heat
+/// index from 26.7°C (80°F) if the humidity is known, and otherwise the
+/// temperature itself, as neither formula applies in between.
+pub fn feels_like(celsius: f32, humidity: Option<f32>, kmh: f32) -> f32 {
+    match humidity {
+        _ if celsius <= 10.0 && kmh > 4.8 => wind_chill(celsius, kmh),
+        Some(humidity) if celsius >= 26.7 => heat_index(celsius, humidity),
+        _ => celsius,
+    }
+}
+
+fn fahrenheit_to_celsius(fahrenheit: f32) -> f32 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn celsius(fahrenheit: f32) -> f32 {
+        fahrenheit_to_celsius(fahrenheit)
+    }
+
+    fn fahrenheit(celsius: f32) -> f32 {
+        celsius * 9.0 / 5.0 + 32.0
+    }
+
+    /// Values from the NWS heat index chart, in °F.
+    #[test]
+    fn heat_index_reference_values() {
+        let cases = [
+            (90.0, 70.0, 106.0),
+            (100.0, 40.0, 109.0),
+            (86.0, 90.0, 105.0),
+            (80.0, 40.0, 80.0),
+        ];
+
+        for (temperature, humidity, expected) in cases {
+            let index = fahrenheit(heat_index(celsius(temperature), humidity));
+
+            assert!(
+                (index - expected).abs() < 1.0,
+                "{}°F at {}%: {}°F",
+                temperature,
+                humidity,
+                index
+            );
+        }
+    }
+
+    /// Values from the NWS and Environment Canada wind chill charts.
+    #[test]
+    fn wind_chill_reference_values() {
+        let cases = [
+            (-10.0, 20.0, -18.0),
+            (-20.0, 30.0, -33.0),
+            (0.0, 10.0, -3.0),
+            (5.0, 40.0, -1.0),
+        ];
+
+        for (temperature, kmh, expected) in cases {
+            let chill = wind_chill(temperature, kmh);
+
+            assert!(
+                (chill - expected).abs() < 0.6,
+                "{}°C at {}km/h: {}°C",
+                temperature,
+                kmh,
+                chill
+            );
+        }
+
+        // 0°F with 15mph of wind.
+        assert!((fahrenheit(wind_chill(celsius(0.0), 15.0 * 1.609_344)) + 19.0).abs() < 0.6);
+    }
+
+    #[test]
+    fn air_temperature_below_the_thresholds() {
+        // Too warm for wind chill, or too little wind.
+        assert_eq!(feels_like(10.5, Some(50.0), 30.0), 10.5);
+        assert_eq!(feels_like(5.0, Some(50.0), 4.8), 5.0);
+        // Too cool for the heat index, or the humidity is unknown.
+        assert_eq!(feels_like(26.6, Some(90.0), 3.0), 26.6);
+        assert_eq!(feels_like(35.0, None, 3.0), 35.0);
+
+        assert_eq!(feels_like(10.0, Some(50.0), 20.0), wind_chill(10.0, 20.0));
+        assert_eq!(feels_like(26.7, Some(90.0), 3.0), heat_index(26.7, 90.0));
+    }
+}
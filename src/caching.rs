@@ -1,49 +1,584 @@
-use crate::{Config, WeatherData};
+//! One cache file per requested location, so switching between places never
+//! shows another place's reading. The location is the one asked for before
+//! gpsd or IP geolocation fill it in, `None` standing for "wherever I am".
+//!
+//! The files live in `WEATHER_CLI_CACHE`, `cache_path` or the platform cache
+//! directory, in that order. Without any of them nothing is cached.
+
+use crate::{Config, ConfigLocation, DailyForecast, WeatherData, duration_format};
 use dirs::cache_dir;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{env, io};
+
+const CACHE_VAR: &str = "WEATHER_CLI_CACHE";
+
+/// A refresh lock older than this is assumed to belong to a crashed process.
+const STALE_LOCK: Duration = Duration::from_secs(5 * 60);
+
+/// One part of a cache entry, with the time it was fetched.
+#[derive(Deserialize, Serialize)]
+struct Section<T> {
+    timestamp: chrono::DateTime<chrono::Local>,
+    data: T,
+}
+
+impl<T> Section<T> {
+    fn new(data: T) -> Self {
+        Self {
+            timestamp: chrono::Local::now(),
+            data,
+        }
+    }
 
+    fn age(&self) -> chrono::Duration {
+        chrono::Local::now().signed_duration_since(self.timestamp)
+    }
+}
+
+/// What changes at most a few times a day: the `--forecast` days and today's
+/// sunrise and sunset.
+#[derive(Deserialize, Serialize, Default)]
+struct Daily {
+    #[serde(default)]
+    forecast: Vec<DailyForecast>,
+    #[serde(default)]
+    sunrise: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    sunset: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// The current conditions expire after `caching_duration`, the daily data
+/// after `daily_caching_duration`, each going by its own timestamp.
 #[derive(Deserialize, Serialize)]
 struct CacheData {
+    /// The location the entry was saved for, checked on load in case two
+    /// locations map to the same file name.
+    #[serde(default)]
+    location: Option<ConfigLocation>,
+    #[serde(default)]
+    current: Option<Section<WeatherData>>,
+    #[serde(default)]
+    daily: Option<Section<Daily>>,
+    /// The reading that was cached before `current`, kept for `--diff`.
+    #[serde(default)]
+    previous: Option<WeatherData>,
+    /// The current conditions as entries written before the split into
+    /// sections hold them, moved into `current` when read.
+    #[serde(default, skip_serializing)]
+    timestamp: Option<chrono::DateTime<chrono::Local>>,
+    #[serde(default, skip_serializing)]
+    data: Option<WeatherData>,
+}
+
+impl CacheData {
+    /// The daily section while it's younger than `daily_caching_duration`.
+    fn fresh_daily(&self, config: &Config) -> Option<&Daily> {
+        self.daily
+            .as_ref()
+            .filter(|daily| daily.age() < config.daily_caching_duration)
+            .map(|daily| &daily.data)
+    }
+
+    /// The current conditions, with the sunrise and sunset filled in from
+    /// the daily section if the provider didn't report them.
+    fn current(self, config: &Config) -> Option<WeatherData> {
+        let daily = self
+            .daily
+            .filter(|daily| daily.age() < config.daily_caching_duration);
+        let mut data = self.current?.data;
+
+        fill_daily(daily.as_ref().map(|daily| &daily.data), &mut data);
+        Some(data)
+    }
+}
+
+fn fill_daily(daily: Option<&Daily>, data: &mut WeatherData) {
+    if let Some(daily) = daily {
+        data.sunrise = data.sunrise.or(daily.sunrise);
+        data.sunset = data.sunset.or(daily.sunset);
+    }
+}
+
+/// `weather-cli` for the geolocated default, otherwise suffixed with the
+/// location, e.g. `weather-cli-berlin-de`, `weather-cli-zip-10115-de` or
+/// `weather-cli-52.5200_13.4000`.
+fn name(location: Option<&ConfigLocation>) -> String {
+    match location {
+        None => "weather-cli".to_string(),
+        Some(ConfigLocation::City(city, country)) => {
+            format!("weather-cli-{}", slug(city, country))
+        }
+        Some(ConfigLocation::PostalCode(code, country)) => {
+            format!("weather-cli-zip-{}", slug(code, country))
+        }
+        Some(ConfigLocation::Coordinates(latitude, longitude)) => {
+            format!("weather-cli-{:.4}_{:.4}", latitude, longitude)
+        }
+    }
+}
+
+fn slug(name: &str, country: &str) -> String {
+    format!("{}-{}", name, country)
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// The coordinates IP geolocation last returned.
+#[derive(Deserialize, Serialize)]
+struct LocationData {
     timestamp: chrono::DateTime<chrono::Local>,
-    data: WeatherData,
+    latitude: f32,
+    longitude: f32,
+    /// The reverse-geocoded name, e.g. `Berlin, Germany`, once looked up.
+    #[serde(default)]
+    place: Option<String>,
+}
+
+fn dir(config: &Config) -> Option<PathBuf> {
+    env::var_os(CACHE_VAR)
+        .map(PathBuf::from)
+        .or_else(|| config.cache_path.clone())
+        .or_else(cache_dir)
+}
+
+fn file(config: &Config, location: Option<&ConfigLocation>) -> Option<PathBuf> {
+    let mut path = dir(config)?;
+
+    path.push(format!("{}.json", name(location)));
+
+    Some(path)
+}
+
+fn lock_file(config: &Config, location: Option<&ConfigLocation>) -> Option<PathBuf> {
+    let mut path = dir(config)?;
+
+    path.push(format!("{}.lock", name(location)));
+
+    Some(path)
+}
+
+/// Reads a JSON cache file, falling back to the TOML file of the same name
+/// that earlier versions wrote until the next save replaces it.
+fn read_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).ok(),
+        Err(_) => {
+            let content = fs::read_to_string(path.with_extension("toml")).ok()?;
+
+            toml::from_str(&content).ok()
+        }
+    }
 }
 
-pub fn save(data: WeatherData) {
-    let file = {
-        let mut path = cache_dir().unwrap();
+/// Writes `data` as JSON, creating the directory first, and removes the TOML
+/// file it replaces. A cache that can't be written only costs the next run a
+/// request, so it's reported, not fatal.
+fn write_file(path: &Path, data: &impl Serialize) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, serde_json::to_string(data).unwrap()));
 
-        path.push("weather-cli.toml");
+    match result {
+        Ok(()) => {
+            let _ = fs::remove_file(path.with_extension("toml"));
+        }
+        Err(err) => eprintln!("Failed to write the cache to {}. {}", path.display(), err),
+    }
+}
+
+fn read(config: &Config, location: Option<&ConfigLocation>) -> Option<CacheData> {
+    let mut cache = read_file::<CacheData>(&file(config, location)?)
+        .filter(|cache| cache.location.as_ref() == location)?;
+
+    if let (None, Some(timestamp), Some(data)) =
+        (&cache.current, cache.timestamp.take(), cache.data.take())
+    {
+        cache.current = Some(Section { timestamp, data });
+    }
+
+    Some(cache)
+}
+
+/// Caches a fresh reading. Its sunrise and sunset also go into the daily
+/// section, which only starts over once it has expired.
+pub fn save(config: &Config, location: Option<&ConfigLocation>, data: WeatherData) {
+    let Some(file) = file(config, location) else {
+        config.log("No cache directory, not caching the reading");
+        return;
+    };
+    let cache = read(config, location);
+    let (previous, mut daily) = match cache {
+        Some(cache) => {
+            let fresh = cache.fresh_daily(config).is_some();
 
-        path
+            (
+                cache.current.map(|current| current.data),
+                cache.daily.filter(|_| fresh),
+            )
+        }
+        None => (None, None),
     };
+
+    if data.sunrise.is_some() || data.sunset.is_some() {
+        let daily = daily.get_or_insert_with(|| Section::new(Daily::default()));
+
+        daily.data.sunrise = data.sunrise;
+        daily.data.sunset = data.sunset;
+    }
+
     let cache_data = CacheData {
-        timestamp: chrono::Local::now(),
-        data,
+        location: location.cloned(),
+        current: Some(Section::new(data)),
+        daily,
+        previous,
+        timestamp: None,
+        data: None,
     };
-    let serialized = toml::to_string(&cache_data).unwrap();
 
-    fs::write(file, serialized).unwrap();
+    write_file(&file, &cache_data);
 }
 
-pub fn load(config: &Config) -> Option<WeatherData> {
-    let file = {
-        let mut path = cache_dir().unwrap();
+/// Caches a fresh `--forecast`, starting the daily section over. The current
+/// conditions are kept as they are.
+pub fn save_forecast(
+    config: &Config,
+    location: Option<&ConfigLocation>,
+    forecast: Vec<DailyForecast>,
+) {
+    let Some(file) = file(config, location) else {
+        config.log("No cache directory, not caching the forecast");
+        return;
+    };
+    let (current, previous, sunrise, sunset) = match read(config, location) {
+        Some(cache) => {
+            let (sunrise, sunset) = cache.daily.as_ref().map_or((None, None), |daily| {
+                (daily.data.sunrise, daily.data.sunset)
+            });
+
+            (cache.current, cache.previous, sunrise, sunset)
+        }
+        None => (None, None, None, None),
+    };
+    let cache_data = CacheData {
+        location: location.cloned(),
+        current,
+        daily: Some(Section::new(Daily {
+            forecast,
+            sunrise,
+            sunset,
+        })),
+        previous,
+        timestamp: None,
+        data: None,
+    };
 
-        path.push("weather-cli.toml");
+    write_file(&file, &cache_data);
+}
 
-        path
+pub fn load(config: &Config, location: Option<&ConfigLocation>) -> Option<WeatherData> {
+    let Some(file) = file(config, location) else {
+        config.log("No cache directory, set cache_path or WEATHER_CLI_CACHE");
+        return None;
     };
-    if !file.exists() {
+    let Some(age) = read(config, location).and_then(|cache| Some(cache.current?.age())) else {
+        config.log(&format!("No cached reading in {}", file.display()));
         return None;
+    };
+
+    if age < config.caching_duration {
+        config.log(&format!(
+            "Cache hit in {}, {} old",
+            file.display(),
+            duration_format::format(&age)
+        ));
+        load_any(config, location)
+    } else {
+        config.log(&format!(
+            "Cached reading in {} expired, {} old",
+            file.display(),
+            duration_format::format(&age)
+        ));
+        None
     }
-    let content = fs::read_to_string(&file).ok()?;
-    let data = toml::from_str::<CacheData>(&content).ok()?;
+}
+
+/// Returns the cached forecast for `days` days from today while the daily
+/// section is fresh and holds that many.
+pub fn load_forecast(
+    config: &Config,
+    location: Option<&ConfigLocation>,
+    days: u8,
+) -> Option<Vec<DailyForecast>> {
+    let daily = read(config, location)?.daily?;
+    let age = daily.age();
+
+    if age >= config.daily_caching_duration {
+        return None;
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let mut forecast = daily.data.forecast;
+
+    forecast.retain(|day| day.date >= today);
+    if forecast.len() < days as usize {
+        return None;
+    }
+    forecast.truncate(days as usize);
+
+    config.log(&format!(
+        "Cached forecast, {} old",
+        duration_format::format(&age)
+    ));
+    Some(forecast)
+}
+
+/// Fills in the sunrise and sunset from the daily section for a fresh reading
+/// from a provider that doesn't report them.
+pub fn complete(config: &Config, location: Option<&ConfigLocation>, data: &mut WeatherData) {
+    if data.sunrise.is_none()
+        && data.sunset.is_none()
+        && let Some(cache) = read(config, location)
+    {
+        fill_daily(cache.fresh_daily(config), data);
+    }
+}
+
+/// Returns an expired reading that's still within `stale_while_revalidate`,
+/// to be shown while a fresh one is fetched.
+pub fn load_stale(config: &Config, location: Option<&ConfigLocation>) -> Option<WeatherData> {
+    let cache = read(config, location)?;
+    let age = cache.current.as_ref()?.age();
+
+    match config.stale_while_revalidate {
+        Some(window)
+            if config
+                .caching_duration
+                .checked_add(&window)
+                .is_some_and(|limit| age >= limit) =>
+        {
+            config.log(&format!(
+                "Cached reading is {} old, too stale to show",
+                duration_format::format(&age)
+            ));
+            None
+        }
+        _ => cache.current(config),
+    }
+}
+
+/// Returns the cached reading regardless of its age.
+pub fn load_any(config: &Config, location: Option<&ConfigLocation>) -> Option<WeatherData> {
+    read(config, location)?.current(config)
+}
+
+/// Returns the reading that was replaced by the currently cached one.
+pub fn load_previous(config: &Config, location: Option<&ConfigLocation>) -> Option<WeatherData> {
+    read(config, location)?.previous
+}
+
+fn location_file(config: &Config) -> Option<PathBuf> {
+    let mut path = dir(config)?;
+
+    path.push("weather-cli-location.json");
+
+    Some(path)
+}
+
+/// Returns the IP-geolocated coordinates if they're younger than
+/// `location_cache_duration`.
+pub fn load_location(config: &Config) -> Option<(f32, f32)> {
+    let data = read_location(config)?;
     let now = chrono::Local::now();
 
-    if now.signed_duration_since(data.timestamp) < config.caching_duration {
-        Some(data.data)
+    if now.signed_duration_since(data.timestamp) < config.location_cache_duration {
+        Some((data.latitude, data.longitude))
     } else {
         None
     }
 }
+
+pub fn save_location(config: &Config, latitude: f32, longitude: f32) {
+    write_location(
+        config,
+        &LocationData {
+            timestamp: chrono::Local::now(),
+            latitude,
+            longitude,
+            place: None,
+        },
+    );
+}
+
+fn read_location(config: &Config) -> Option<LocationData> {
+    read_file(&location_file(config)?)
+}
+
+fn write_location(config: &Config, data: &LocationData) {
+    if let Some(file) = location_file(config) {
+        write_file(&file, data);
+    }
+}
+
+/// Returns the place name cached with the geolocated coordinates, if they
+/// match the ones given.
+pub fn load_place(config: &Config, latitude: f32, longitude: f32) -> Option<String> {
+    read_location(config)
+        .filter(|data| data.latitude == latitude && data.longitude == longitude)?
+        .place
+}
+
+/// Stores the place name with the geolocated coordinates. Other coordinates,
+/// such as a gpsd fix, aren't cached.
+pub fn save_place(config: &Config, latitude: f32, longitude: f32, place: &str) {
+    if let Some(mut data) = read_location(config)
+        .filter(|data| data.latitude == latitude && data.longitude == longitude)
+    {
+        data.place = Some(place.to_string());
+        write_location(config, &data);
+    }
+}
+
+/// Takes the background refresh lock for `location`, returning `false` if
+/// another refresh is already running or there's no cache to refresh.
+pub fn lock_refresh(config: &Config, location: Option<&ConfigLocation>) -> bool {
+    let Some(file) = lock_file(config, location) else {
+        return false;
+    };
+    let stale = fs::metadata(&file)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age > STALE_LOCK)
+        });
+
+    if stale {
+        let _ = fs::remove_file(&file);
+    }
+
+    if let Some(parent) = file.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(file)
+        .is_ok()
+}
+
+pub fn unlock_refresh(config: &Config, location: Option<&ConfigLocation>) {
+    if let Some(file) = lock_file(config, location) {
+        let _ = fs::remove_file(file);
+    }
+}
+
+/// Removes the cache entries for every location, and the geolocated
+/// coordinates, returning how many there were.
+pub fn clear(config: &Config) -> io::Result<usize> {
+    let entries = match dir(config).map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        Some(Err(err)) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Some(Err(err)) => return Err(err),
+        None => return Ok(0),
+    };
+    let mut removed = 0;
+
+    for entry in entries {
+        let path = entry?.path();
+        let is_cache = path
+            .extension()
+            .is_some_and(|extension| extension == "json" || extension == "toml")
+            && path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem == "weather-cli" || stem.starts_with("weather-cli-"));
+
+        if is_cache {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str) -> Config {
+        let dir = env::temp_dir().join(format!("weather-cli-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        Config {
+            cache_path: Some(dir),
+            ..Config::default()
+        }
+    }
+
+    fn reading(sunrise: Option<&str>) -> WeatherData {
+        serde_json::from_value(serde_json::json!({
+            "temperature": "20°C",
+            "feels_like": "19°C",
+            "wind_speed": "10km/h",
+            "wind_direction": "N",
+            "condition": "Clear",
+            "sunrise": sunrise,
+            "sunset": sunrise,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn daily_section_outlives_the_current_one() {
+        let mut config = config("daily");
+        let location = ConfigLocation::Coordinates(1.0, 2.0);
+
+        save(
+            &config,
+            Some(&location),
+            reading(Some("2024-03-14T06:00:00Z")),
+        );
+        save(&config, Some(&location), reading(None));
+
+        let cached = load(&config, Some(&location)).unwrap();
+        assert!(
+            cached.sunrise.is_some(),
+            "sunrise kept from the daily section"
+        );
+
+        config.caching_duration = chrono::Duration::zero();
+        assert!(load(&config, Some(&location)).is_none());
+
+        config.daily_caching_duration = chrono::Duration::zero();
+        assert!(
+            load_any(&config, Some(&location))
+                .unwrap()
+                .sunrise
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn reads_entries_written_before_the_split() {
+        let config = config("legacy");
+        let path = file(&config, None).unwrap();
+        let legacy = serde_json::json!({
+            "timestamp": chrono::Local::now(),
+            "data": reading(None),
+        });
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        assert_eq!(load(&config, None).unwrap().temperature, "20°C");
+    }
+}
@@ -0,0 +1,57 @@
+//! Current position from a local `gpsd`, read from the first TPV report with
+//! a fix on its JSON socket at `localhost:2947`.
+
+#[cfg(feature = "gpsd")]
+pub fn position() -> Result<(f32, f32), String> {
+    use serde::Deserialize;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[derive(Deserialize)]
+    struct Report {
+        class: String,
+        #[serde(default)]
+        mode: u8,
+        lat: Option<f32>,
+        lon: Option<f32>,
+    }
+
+    let mut stream = TcpStream::connect("localhost:2947").map_err(|err| err.to_string())?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(|err| err.to_string())?;
+    stream
+        .write_all(b"?WATCH={\"enable\":true,\"json\":true};\n")
+        .map_err(|err| err.to_string())?;
+
+    let started = Instant::now();
+    let mut lines = BufReader::new(stream).lines();
+
+    while started.elapsed() < TIMEOUT {
+        let line = match lines.next() {
+            Some(line) => line.map_err(|err| err.to_string())?,
+            None => break,
+        };
+        let Ok(report) = serde_json::from_str::<Report>(&line) else {
+            continue;
+        };
+
+        // Mode 2 and 3 are 2D and 3D fixes, anything below has no position.
+        if report.class == "TPV"
+            && report.mode >= 2
+            && let (Some(latitude), Some(longitude)) = (report.lat, report.lon)
+        {
+            return Ok((latitude, longitude));
+        }
+    }
+
+    Err("no position fix from gpsd".to_string())
+}
+
+#[cfg(not(feature = "gpsd"))]
+pub fn position() -> Result<(f32, f32), String> {
+    Err("built without the `gpsd` feature".to_string())
+}
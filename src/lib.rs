@@ -0,0 +1,2419 @@
+use chrono::Duration;
+use chrono::format::{Item, StrftimeItems};
+use reqwest::blocking;
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::thread;
+use std::{env, fs};
+
+pub mod alert;
+pub mod apparent;
+pub mod caching;
+pub mod gpsd;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod moon;
+pub mod providers;
+pub mod secrets;
+pub mod timespec;
+
+/// Overrides the configured location, e.g. `WEATHER_CLI_LOCATION="Berlin,DE"`.
+pub const LOCATION_VAR: &str = "WEATHER_CLI_LOCATION";
+/// The API key, taking precedence over `api_key_file` and the config.
+const API_KEY_VAR: &str = "WEATHER_CLI_API_KEY";
+
+/// Met.no and the NWS reject requests without an identifying User-Agent and
+/// ask for a way to reach the author in it.
+const DEFAULT_USER_AGENT: &str = concat!(
+    "weathercli/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/Vbuuu/weathercli)"
+);
+
+mod duration_format {
+    use crate::parse_duration;
+    use chrono::Duration;
+    use serde::{Deserializer, Serializer, de};
+    use std::fmt;
+    use std::fmt::Formatter;
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(duration))
+    }
+
+    /// Formats a duration the way it's written in the config, e.g. `1h30min`.
+    pub fn format(duration: &Duration) -> String {
+        let seconds = duration.num_seconds();
+        let parts = [
+            (seconds / (24 * 60 * 60), "d"),
+            (seconds / (60 * 60) % 24, "h"),
+            (seconds / 60 % 60, "min"),
+            (seconds % 60, "s"),
+        ];
+        let formatted: String = parts
+            .iter()
+            .filter(|(amount, _)| *amount > 0)
+            .map(|(amount, unit)| format!("{}{}", amount, unit))
+            .collect();
+
+        if formatted.is_empty() {
+            "0min".to_string()
+        } else {
+            formatted
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DurationVisitor;
+
+        impl de::Visitor<'_> for DurationVisitor {
+            type Value = Duration;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                formatter
+                    .write_str("a duration formatted as '2d', '1h', '30min', '10s' or '1h30min'")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                parse_duration(value).ok_or_else(|| E::custom("failed to parse duration"))
+            }
+        }
+
+        deserializer.deserialize_str(DurationVisitor)
+    }
+}
+
+/// [`duration_format`] for durations that may be left out.
+mod optional_duration_format {
+    use chrono::Duration;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => super::duration_format::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::duration_format::deserialize(deserializer).map(Some)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ConfigWeatherProvider {
+    #[default]
+    #[serde(rename = "open-meteo")]
+    OpenMeteo,
+    #[serde(rename = "open-weather-map")]
+    OpenWeatherMap,
+    #[serde(rename = "met-no")]
+    MetNo,
+    #[serde(rename = "weather-api")]
+    WeatherApi,
+    #[serde(rename = "nws")]
+    NationalWeatherService,
+    #[serde(rename = "wttr")]
+    Wttr,
+}
+
+/// A postal code has the same shape as a city, so it's the one tagged variant:
+/// `location = { postal_code = ["10115", "DE"] }`.
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub enum ConfigLocation {
+    #[serde(rename = "postal_code")]
+    PostalCode(String, String), // Postal code, Country
+    #[serde(untagged)]
+    City(String, String), // City, Country
+    #[serde(untagged)]
+    Coordinates(f32, f32), // Latitude, Longitude
+}
+
+/// Formats the location the way [`FromStr`] parses it, e.g. `Berlin,DE`.
+impl Display for ConfigLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigLocation::PostalCode(code, country) => write!(f, "zip:{},{}", code, country),
+            ConfigLocation::City(city, country) => write!(f, "{},{}", city, country),
+            ConfigLocation::Coordinates(latitude, longitude) => {
+                write!(f, "{},{}", latitude, longitude)
+            }
+        }
+    }
+}
+
+/// Parses `"52.52,13.40"` as coordinates, `"zip:10115,DE"` as a postal code
+/// and anything else of the form `"Berlin,DE"` as a city and country code.
+impl FromStr for ConfigLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s
+            .rsplit_once(',')
+            .map(|(first, second)| (first.trim(), second.trim()))
+            .filter(|(first, second)| !first.is_empty() && !second.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "expected 'lat,lon', 'City,CC' or 'zip:CODE,CC', got '{}'",
+                    s
+                )
+            })?;
+
+        if let Some(code) = first.strip_prefix("zip:").map(str::trim) {
+            if code.is_empty() {
+                return Err(format!("expected a postal code after 'zip:', got '{}'", s));
+            }
+
+            return Ok(ConfigLocation::PostalCode(
+                code.to_string(),
+                second.to_string(),
+            ));
+        }
+
+        match (first.parse(), second.parse()) {
+            (Ok(latitude), Ok(longitude)) => Ok(ConfigLocation::Coordinates(latitude, longitude)),
+            _ => Ok(ConfigLocation::City(first.to_string(), second.to_string())),
+        }
+    }
+}
+
+/// A named entry under `[locations]`, picked with `--profile`.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ConfigProfile {
+    pub location: ConfigLocation,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigUnits {
+    Metric,
+    Imperial,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum ConfigTimeFormat {
+    #[serde(rename = "24h")]
+    _24H,
+    #[serde(rename = "12h")]
+    _12H,
+    /// 12h or 24h depending on the locale in `LC_ALL`, `LC_TIME` or `LANG`.
+    #[serde(rename = "auto")]
+    Auto,
+}
+
+/// How the wind arrow is oriented. Providers report the direction the wind is
+/// coming *from*, so `From` points at the source and `To` points downwind.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigWindArrow {
+    From,
+    To,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigTemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigWindUnit {
+    Kmh,
+    Mph,
+    Ms,
+    Knots,
+    Beaufort,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+pub enum ConfigPressureUnit {
+    #[default]
+    #[serde(rename = "hPa")]
+    HPa,
+    #[serde(rename = "inHg")]
+    InHg,
+    #[serde(rename = "mmHg")]
+    MmHg,
+    #[serde(rename = "kPa")]
+    KPa,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigCacheMode {
+    /// Refetch synchronously once the cache is older than `caching_duration`.
+    #[default]
+    Ttl,
+    /// Print an expired cache entry immediately and refresh it in the background.
+    Swr,
+}
+
+/// Where the feels-like temperature comes from.
+#[derive(Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFeelsLike {
+    /// The provider's own value, computed locally only where there is none.
+    #[default]
+    Provider,
+    /// Wind chill or heat index from the reading, see [`apparent::feels_like`].
+    Computed,
+}
+
+/// Where the location comes from when `location` isn't set.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLocationSource {
+    /// Geolocate the public IP address.
+    #[default]
+    Ip,
+    /// Ask a local `gpsd`, falling back to the IP address if it has no fix.
+    Gpsd,
+}
+
+/// How the unit is appended to temperatures, e.g. `20°C`, `20°`, `20C` or `20 °C`.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigTemperatureSuffixStyle {
+    #[default]
+    DegreeLetter,
+    DegreeOnly,
+    LetterOnly,
+    Spaced,
+}
+
+/// Resolution of the compass used to name the wind direction.
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum ConfigCompassPoints {
+    Four,
+    Eight,
+    #[default]
+    Sixteen,
+}
+
+/// When to color the default output with ANSI escapes.
+#[derive(Deserialize, Serialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigColor {
+    Always,
+    /// Color only when stdout is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Never,
+}
+
+/// A part of the default output. The temperature, condition and time fill the
+/// left column, the others are lines of their own.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigField {
+    #[serde(alias = "temp")]
+    Temperature,
+    FeelsLike,
+    Condition,
+    Wind,
+    Humidity,
+    #[serde(alias = "dewpoint")]
+    DewPoint,
+    Pressure,
+    Visibility,
+    #[serde(alias = "cloud-cover")]
+    CloudCover,
+    UvIndex,
+    Precipitation,
+    /// New snow and snow depth, while it's snowing or there's snow.
+    Snow,
+    /// Sunrise and sunset.
+    Sun,
+    Moon,
+    /// One line per active alert.
+    Alerts,
+    /// The geolocated place, left out for configured locations.
+    Location,
+    AirQuality,
+    Time,
+    Provider,
+}
+
+/// Cutoffs, in the configured temperature unit, for the thermometer icon shown
+/// in front of the temperature.
+#[derive(Deserialize, Serialize)]
+pub struct ConfigTemperatureThresholds {
+    pub cold: f32,
+    pub hot: f32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Config {
+    #[serde(default)]
+    pub provider: ConfigWeatherProvider,
+    /// Tried in order until one answers, taking the place of `provider`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<ConfigWeatherProvider>,
+    pub api_key: Option<String>,
+    pub api_key_keyring: Option<String>,
+    /// A file holding just the API key, e.g. a mounted secret. It takes
+    /// precedence over `api_key_keyring` and `api_key`.
+    pub api_key_file: Option<PathBuf>,
+    pub user_agent: Option<String>,
+    pub location: Option<ConfigLocation>,
+    /// Named locations such as `[locations.work]`, used instead of `location`
+    /// with `--profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<String, ConfigProfile>,
+    /// A TOML or CSV file with more named locations, merged into `locations`
+    /// by [`Config::load_locations_file`]. Entries in the config win.
+    pub locations_file: Option<PathBuf>,
+    #[serde(default)]
+    pub location_source: ConfigLocationSource,
+    /// Whether an unset location may be looked up from the IP address. If not,
+    /// only gpsd is asked.
+    #[serde(default = "default_allow_ip_geolocation")]
+    pub allow_ip_geolocation: bool,
+    pub units: ConfigUnits,
+    pub time_format: ConfigTimeFormat,
+    /// A strftime pattern such as `%a %H:%M`, overriding `time_format`.
+    pub time_format_string: Option<String>,
+    /// An IANA name such as `Europe/Berlin` to show times in instead of the
+    /// system's zone, or `auto` for the location's own, as far as the provider
+    /// reports it.
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub temperature_suffix_style: ConfigTemperatureSuffixStyle,
+    /// Decimals temperatures are rounded to.
+    #[serde(default)]
+    pub temperature_precision: u8,
+    #[serde(default)]
+    pub feels_like: ConfigFeelsLike,
+    /// Width of the left column in the default, forecast and hourly output.
+    /// Unset fits the widest temperature, condition or time, at least 14.
+    #[serde(default)]
+    pub column_width: Option<usize>,
+    #[serde(with = "duration_format")]
+    pub caching_duration: Duration,
+    #[serde(default)]
+    pub cache_mode: ConfigCacheMode,
+    /// How long past `caching_duration` `cache_mode = "swr"` still shows a
+    /// reading, without limit if unset.
+    #[serde(
+        default,
+        with = "optional_duration_format",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stale_while_revalidate: Option<Duration>,
+    /// How long the forecast, sunrise and sunset are cached, independently of
+    /// the current conditions.
+    #[serde(with = "duration_format", default = "default_daily_caching_duration")]
+    pub daily_caching_duration: Duration,
+    /// How long IP-geolocated coordinates are reused before looking them up again.
+    #[serde(with = "duration_format", default = "default_location_cache_duration")]
+    pub location_cache_duration: Duration,
+    /// How long a single request may take before it's abandoned.
+    #[serde(with = "duration_format", default = "default_request_timeout")]
+    pub request_timeout: Duration,
+    /// How often a request is retried after a connection failure or timeout.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// The wait before the first retry, doubled for each further one.
+    #[serde(with = "duration_format", default = "default_retry_delay")]
+    pub retry_delay: Duration,
+    pub wind_arrow: Option<ConfigWindArrow>,
+    pub temperature_icon_thresholds: Option<ConfigTemperatureThresholds>,
+    #[serde(default)]
+    pub color: ConfigColor,
+    /// A single-line layout such as `{temp} {condition}`, like `--format`.
+    pub format: Option<String>,
+    /// The parts of the default output, in this order, instead of all of them.
+    /// Listing an optional one such as `moon` shows it without `show_moon`.
+    pub fields: Option<Vec<ConfigField>>,
+    /// Add a line with the air quality, like `--air-quality`.
+    #[serde(default)]
+    pub show_air_quality: bool,
+    /// Add a line for each active weather alert, like `--alerts`.
+    #[serde(default)]
+    pub show_alerts: bool,
+    /// Add a line with the moon phase, like `--moon`.
+    #[serde(default)]
+    pub show_moon: bool,
+    /// Add a line with the dew point, like `--show dew_point`.
+    #[serde(default)]
+    pub show_dew_point: bool,
+    /// Add a line with the cloud cover, like `--show cloud_cover`.
+    #[serde(default)]
+    pub show_cloud_cover: bool,
+    /// Prefix the condition with an emoji such as ☀️ or 🌧️.
+    #[serde(default)]
+    pub show_icons: bool,
+    #[serde(default)]
+    pub pressure_unit: ConfigPressureUnit,
+    /// Overrides the temperature unit implied by `units`.
+    pub temperature_unit: Option<ConfigTemperatureUnit>,
+    /// Overrides the wind speed unit implied by `units`.
+    pub wind_unit: Option<ConfigWindUnit>,
+    #[serde(default)]
+    pub wind_direction_points: ConfigCompassPoints,
+    /// The directory for the cache files, instead of the platform's cache
+    /// directory. `WEATHER_CLI_CACHE` takes precedence.
+    pub cache_path: Option<PathBuf>,
+    /// Set by `--verbose`, never read from the file.
+    #[serde(skip)]
+    pub verbose: bool,
+    /// Where `location` was overridden, e.g. `from --location`, for
+    /// `--verbose`. Unset while it's the one from the file.
+    #[serde(skip)]
+    pub location_origin: Option<String>,
+    /// Built by the first request and shared by the rest, see [`Config::client`].
+    #[serde(skip)]
+    client: OnceLock<blocking::Client>,
+    /// The non-blocking counterpart of `client`, see [`Config::async_client`].
+    #[cfg(feature = "async")]
+    #[serde(skip)]
+    async_client: OnceLock<reqwest::Client>,
+}
+
+/// Coordinates plus the metadata a geocoding lookup returned for them.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ResolvedLocation {
+    pub latitude: f32,
+    pub longitude: f32,
+    pub name: String,
+    pub admin1: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<String>,
+}
+
+/// One day of a multi-day forecast, with temperatures formatted like [`WeatherData`]'s.
+#[derive(Deserialize, Serialize)]
+pub struct DailyForecast {
+    pub date: chrono::NaiveDate,
+    pub high: String,
+    pub low: String,
+    pub condition: WeatherCondition,
+}
+
+/// One hour of an hourly forecast, with the temperature formatted like [`WeatherData`]'s.
+#[derive(Deserialize, Serialize)]
+pub struct HourlyForecast {
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// The location's UTC offset in seconds, like [`WeatherData::utc_offset`].
+    pub utc_offset: Option<i32>,
+    pub temperature: String,
+    pub condition: WeatherCondition,
+}
+
+/// A severe weather warning issued by a government agency.
+#[derive(Deserialize, Serialize)]
+pub struct WeatherAlert {
+    /// The kind of warning, e.g. `Winter Storm Warning`.
+    pub event: String,
+    pub headline: Option<String>,
+}
+
+/// Current air quality, fetched separately from the weather.
+#[derive(Deserialize, Serialize)]
+pub struct AirQuality {
+    /// The European Air Quality Index, 0 (good) to 100 and above (extremely poor).
+    pub european_aqi: Option<u16>,
+    /// Fine particulate matter in µg/m³.
+    pub pm2_5: Option<f32>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WeatherData {
+    pub temperature: String,
+    pub feels_like: String,
+    pub wind_speed: String,
+    /// The gust speed, formatted like `wind_speed`, for providers that report one.
+    #[serde(default)]
+    pub wind_gust: Option<String>,
+    pub wind_direction: String,
+    #[serde(default)]
+    pub wind_degrees: i16,
+    /// Relative humidity in percent.
+    #[serde(default)]
+    pub humidity: Option<u8>,
+    /// The dew point, formatted like `temperature`. Open-Meteo reports it, for
+    /// the other providers it's derived from the temperature and humidity.
+    #[serde(default)]
+    pub dew_point: Option<String>,
+    /// Surface pressure in hPa, converted to `pressure_unit` when displayed.
+    #[serde(default)]
+    pub pressure: Option<f32>,
+    /// Visibility in meters, shown in km or miles depending on `units`.
+    #[serde(default)]
+    pub visibility: Option<f32>,
+    /// Cloud cover in percent.
+    #[serde(default)]
+    pub cloud_cover: Option<u8>,
+    /// UV index, for providers that report one.
+    #[serde(default)]
+    pub uv_index: Option<f32>,
+    /// Precipitation in mm, converted to inches for `units = "imperial"` when
+    /// displayed.
+    #[serde(default)]
+    pub precipitation: Option<f32>,
+    /// The chance of precipitation in percent, today's highest for Open-Meteo
+    /// and the coming hour's for the NWS.
+    #[serde(default)]
+    pub precipitation_probability: Option<u8>,
+    /// Snowfall in cm over the past hour, converted to inches for
+    /// `units = "imperial"` when displayed.
+    #[serde(default)]
+    pub snowfall: Option<f32>,
+    /// The depth of the snow on the ground in cm.
+    #[serde(default)]
+    pub snow_depth: Option<f32>,
+    /// Today's sunrise and sunset, formatted with `time_format` when displayed.
+    #[serde(default)]
+    pub sunrise: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub sunset: Option<chrono::DateTime<chrono::Utc>>,
+    /// The location's UTC offset in seconds, used for `timezone = "auto"`.
+    #[serde(default)]
+    pub utc_offset: Option<i32>,
+    /// Whether the sun is up, for providers that say so.
+    #[serde(default)]
+    pub is_day: Option<bool>,
+    pub condition: WeatherCondition,
+    /// The provider-specific code `condition` was mapped from.
+    #[serde(default)]
+    pub condition_code: Option<i32>,
+    #[serde(default)]
+    pub location: Option<ResolvedLocation>,
+}
+
+impl WeatherData {
+    /// Whether it's day, as reported or else going by today's sunrise and
+    /// sunset. Without either it's taken to be day.
+    pub fn is_daytime(&self) -> bool {
+        let now = chrono::Utc::now();
+
+        match (self.is_day, self.sunrise, self.sunset) {
+            (Some(is_day), _, _) => is_day,
+            (None, Some(sunrise), Some(sunset)) => (sunrise..sunset).contains(&now),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum WeatherCondition {
+    Clear,
+    PartlyCloudy,
+    Overcast,
+    Foggy,
+    Drizzle,
+    Rainy,
+    Snowy,
+    SnowGrains,
+    RainShowers,
+    SnowShowers,
+    Thunderstorms,
+    Unknown,
+}
+
+/// Which glyphs to use for icons and arrows.
+#[derive(Clone, Copy, PartialEq)]
+pub enum IconSet {
+    Emoji,
+    /// Plain text for terminals or fonts that can't render emoji.
+    Ascii,
+}
+
+fn default_request_timeout() -> Duration {
+    Duration::seconds(10)
+}
+
+fn default_allow_ip_geolocation() -> bool {
+    true
+}
+
+fn default_daily_caching_duration() -> Duration {
+    Duration::hours(12)
+}
+
+fn default_location_cache_duration() -> Duration {
+    Duration::hours(6)
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_delay() -> Duration {
+    Duration::seconds(1)
+}
+
+#[derive(Deserialize)]
+struct MullvadResponse {
+    latitude: f32,
+    longitude: f32,
+}
+
+#[derive(Deserialize)]
+struct IpInfoResponse {
+    /// `"latitude,longitude"`
+    loc: String,
+}
+
+#[derive(Deserialize)]
+struct IpApiResponse {
+    status: String,
+    message: Option<String>,
+    lat: Option<f32>,
+    lon: Option<f32>,
+}
+
+/// BigDataCloud's free client-side reverse geocoder, which needs no key.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReverseGeocodeResponse {
+    #[serde(default)]
+    city: String,
+    #[serde(default)]
+    locality: String,
+    #[serde(default)]
+    country_name: String,
+}
+
+type Geolocation = fn(&Config) -> Result<(f32, f32), String>;
+
+/// IP geolocation services, tried in order until one answers.
+const GEOLOCATION_SERVICES: [(&str, Geolocation); 3] = [
+    ("am.i.mullvad.net", mullvad), // Seems to give the best results
+    ("ipinfo.io", ip_info),
+    ("ip-api.com", ip_api),
+];
+
+fn mullvad(config: &Config) -> Result<(f32, f32), String> {
+    let res: MullvadResponse = config
+        .get("https://ipv6.am.i.mullvad.net/json")
+        .and_then(|res| res.json())
+        .map_err(|err| err.to_string())?;
+
+    Ok((res.latitude, res.longitude))
+}
+
+fn ip_info(config: &Config) -> Result<(f32, f32), String> {
+    let res: IpInfoResponse = config
+        .get("https://ipinfo.io/json")
+        .and_then(|res| res.json())
+        .map_err(|err| err.to_string())?;
+
+    match ConfigLocation::from_str(&res.loc) {
+        Ok(ConfigLocation::Coordinates(latitude, longitude)) => Ok((latitude, longitude)),
+        _ => Err(format!("unexpected loc {:?}", res.loc)),
+    }
+}
+
+fn ip_api(config: &Config) -> Result<(f32, f32), String> {
+    // HTTPS is reserved for paying customers.
+    let res: IpApiResponse = config
+        .get("http://ip-api.com/json")
+        .and_then(|res| res.json())
+        .map_err(|err| err.to_string())?;
+
+    match (res.status.as_str(), res.lat, res.lon) {
+        ("success", Some(latitude), Some(longitude)) => Ok((latitude, longitude)),
+        _ => Err(res.message.unwrap_or(res.status)),
+    }
+}
+
+/// Whether `pattern` is a strftime pattern chrono can format with.
+fn valid_time_format(pattern: &str) -> bool {
+    !StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error))
+}
+
+/// Masks API keys in a URL's query, so `--verbose` and `--dry-run` output can
+/// be shared.
+pub fn redact(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name @ ("appid" | "key"), _)) => format!("{}=***", name),
+            _ => pair.to_string(),
+        })
+        .collect();
+
+    format!("{}?{}", base, query.join("&"))
+}
+
+/// No location is set and none could be looked up.
+#[derive(Debug)]
+pub enum LocationError {
+    /// `allow_ip_geolocation` is off, so no service was asked.
+    GeolocationDisabled,
+    /// Every IP geolocation service failed, with the reason for each.
+    ServicesFailed(Vec<(&'static str, String)>),
+}
+
+impl Display for LocationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocationError::GeolocationDisabled => write!(
+                f,
+                "No location is set and IP geolocation is turned off, set location in the config or pass --location."
+            ),
+            LocationError::ServicesFailed(failures) => {
+                write!(
+                    f,
+                    "Failed to look up the location of your IP address, set location in the config."
+                )?;
+
+                for (service, reason) in failures {
+                    write!(f, "\n  {}: {}", service, reason)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocationError {}
+
+/// A setting that can't work, found by [`Config::validate`] before any request.
+#[derive(Debug)]
+pub enum ConfigError {
+    MissingApiKey(ConfigWeatherProvider),
+    /// `api_key_file` couldn't be read, with the reason.
+    UnreadableApiKeyFile(PathBuf, String),
+    /// Coordinates out of range, with the setting they came from, e.g. `locations.work`.
+    InvalidCoordinates(String, f32, f32),
+    ZeroCachingDuration,
+    /// `locations_file` couldn't be read or parsed, with the line if known.
+    InvalidLocationsFile(PathBuf, Option<usize>, String),
+    /// A `timezone` that's neither `auto` nor a known IANA name.
+    UnknownTimezone(String),
+    /// A `time_format_string` that isn't a valid strftime pattern.
+    InvalidTimeFormatString(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::MissingApiKey(provider) => write!(
+                f,
+                "{} needs an API key, set {}, api_key_file, api_key_keyring or api_key.",
+                provider, API_KEY_VAR
+            ),
+            ConfigError::UnreadableApiKeyFile(path, err) => {
+                write!(f, "Failed to read api_key_file {}. {}", path.display(), err)
+            }
+            ConfigError::InvalidCoordinates(setting, latitude, longitude) => write!(
+                f,
+                "{} = [{}, {}] is out of range, the latitude must be within -90 to 90 and the longitude within -180 to 180.",
+                setting, latitude, longitude
+            ),
+            ConfigError::ZeroCachingDuration => {
+                write!(f, "caching_duration must be longer than 0.")
+            }
+            ConfigError::InvalidLocationsFile(path, Some(line), err) => write!(
+                f,
+                "Failed to parse locations_file {}, line {}. {}",
+                path.display(),
+                line,
+                err
+            ),
+            ConfigError::InvalidLocationsFile(path, None, err) => {
+                write!(
+                    f,
+                    "Failed to read locations_file {}. {}",
+                    path.display(),
+                    err
+                )
+            }
+            ConfigError::UnknownTimezone(timezone) => write!(
+                f,
+                "timezone = \"{}\" is unknown, expected \"auto\" or a name such as \"Europe/Berlin\".",
+                timezone
+            ),
+            ConfigError::InvalidTimeFormatString(pattern) => write!(
+                f,
+                "time_format_string = \"{}\" isn't a valid strftime pattern, such as \"%a %H:%M\".",
+                pattern
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            api_key_keyring: None,
+            api_key_file: None,
+            user_agent: None,
+            provider: ConfigWeatherProvider::OpenMeteo,
+            providers: Vec::new(),
+            location: None,
+            locations: BTreeMap::new(),
+            locations_file: None,
+            location_source: ConfigLocationSource::Ip,
+            units: ConfigUnits::Metric,
+            time_format: ConfigTimeFormat::_24H,
+            time_format_string: None,
+            timezone: None,
+            temperature_suffix_style: ConfigTemperatureSuffixStyle::DegreeLetter,
+            temperature_precision: 0,
+            feels_like: ConfigFeelsLike::Provider,
+            column_width: None,
+            caching_duration: Duration::hours(1),
+            cache_mode: ConfigCacheMode::Ttl,
+            stale_while_revalidate: None,
+            daily_caching_duration: default_daily_caching_duration(),
+            location_cache_duration: default_location_cache_duration(),
+            allow_ip_geolocation: default_allow_ip_geolocation(),
+            request_timeout: default_request_timeout(),
+            max_retries: default_max_retries(),
+            retry_delay: default_retry_delay(),
+            wind_arrow: None,
+            temperature_icon_thresholds: None,
+            color: ConfigColor::Auto,
+            show_icons: false,
+            show_air_quality: false,
+            show_moon: false,
+            show_dew_point: false,
+            show_cloud_cover: false,
+            show_alerts: false,
+            format: None,
+            fields: None,
+            pressure_unit: ConfigPressureUnit::HPa,
+            temperature_unit: None,
+            wind_unit: None,
+            wind_direction_points: ConfigCompassPoints::Sixteen,
+            cache_path: None,
+            verbose: false,
+            location_origin: None,
+            client: OnceLock::new(),
+            #[cfg(feature = "async")]
+            async_client: OnceLock::new(),
+        }
+    }
+}
+
+impl Display for ConfigWeatherProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "https://{}",
+            match self {
+                ConfigWeatherProvider::OpenMeteo => "open-meteo.com".to_string(),
+                ConfigWeatherProvider::OpenWeatherMap => "openweathermap.org".to_string(),
+                ConfigWeatherProvider::MetNo => "api.met.no".to_string(),
+                ConfigWeatherProvider::WeatherApi => "weatherapi.com".to_string(),
+                ConfigWeatherProvider::NationalWeatherService => "weather.gov".to_string(),
+                ConfigWeatherProvider::Wttr => "wttr.in".to_string(),
+            }
+        )
+    }
+}
+
+impl Display for ConfigUnits {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ConfigUnits::Metric => "metric",
+                ConfigUnits::Imperial => "imperial",
+            }
+        )
+    }
+}
+
+impl Display for WeatherCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WeatherCondition::Clear => "Clear",
+                WeatherCondition::PartlyCloudy => "Partly Cloudy",
+                WeatherCondition::Overcast => "Overcast",
+                WeatherCondition::Foggy => "Foggy",
+                WeatherCondition::Drizzle => "Drizzle",
+                WeatherCondition::Rainy => "Rainy",
+                WeatherCondition::Snowy => "Snowy",
+                WeatherCondition::SnowGrains => "Snow Grains",
+                WeatherCondition::RainShowers => "Showers",
+                WeatherCondition::SnowShowers => "Showers",
+                WeatherCondition::Thunderstorms => "Thunderstorm",
+                WeatherCondition::Unknown => "Unknown",
+            }
+        )
+    }
+}
+
+impl Config {
+    /// Merges the named locations from `locations_file` into `locations`,
+    /// keeping the ones defined in the config itself. A `.csv` file has one
+    /// `name,latitude,longitude` or `name,city,country` per line, anything
+    /// else is read as TOML tables like `[locations.work]` without the prefix.
+    pub fn load_locations_file(&mut self) -> Result<(), ConfigError> {
+        let Some(path) = &self.locations_file else {
+            return Ok(());
+        };
+        let invalid =
+            |line, err: String| ConfigError::InvalidLocationsFile(path.clone(), line, err);
+        let content = fs::read_to_string(path).map_err(|err| invalid(None, err.to_string()))?;
+        let locations = if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+        {
+            parse_locations_csv(&content).map_err(|(line, err)| invalid(Some(line), err))?
+        } else {
+            toml::from_str::<BTreeMap<String, ConfigProfile>>(&content).map_err(|err| {
+                let line = err
+                    .span()
+                    .map(|span| content[..span.start].lines().count().max(1));
+
+                invalid(line, err.message().to_string())
+            })?
+        };
+
+        for (name, profile) in locations {
+            self.locations.entry(name).or_insert(profile);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the configured `location` with `value`, the content of
+    /// `WEATHER_CLI_LOCATION`, if set.
+    pub fn apply_env(&mut self, value: Option<&str>) {
+        if let Some(value) = value {
+            match value.parse() {
+                Ok(location) => {
+                    self.location = Some(location);
+                    self.location_origin = Some(format!("from {}", LOCATION_VAR));
+                }
+                Err(err) => eprintln!("Ignoring {}. {}", LOCATION_VAR, err),
+            }
+        }
+    }
+
+    /// Checks the settings that would otherwise only fail once a request is
+    /// made, reporting every problem rather than the first.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        for provider in self.provider_chain() {
+            let needs_key = matches!(
+                provider,
+                ConfigWeatherProvider::OpenWeatherMap | ConfigWeatherProvider::WeatherApi
+            );
+
+            if needs_key
+                && env_api_key().is_none()
+                && self.api_key_file.is_none()
+                && self.api_key_keyring.is_none()
+                && self.api_key.is_none()
+            {
+                errors.push(ConfigError::MissingApiKey(provider));
+            }
+        }
+
+        // An unreadable file doesn't matter while the environment overrides it.
+        if let Some(path) = &self.api_key_file
+            && env_api_key().is_none()
+            && let Err(err) = fs::read_to_string(path)
+        {
+            errors.push(ConfigError::UnreadableApiKeyFile(
+                path.clone(),
+                err.to_string(),
+            ));
+        }
+
+        let locations = self
+            .location
+            .iter()
+            .map(|location| ("location".to_string(), location))
+            .chain(
+                self.locations
+                    .iter()
+                    .map(|(name, profile)| (format!("locations.{}", name), &profile.location)),
+            );
+
+        for (setting, location) in locations {
+            if let ConfigLocation::Coordinates(latitude, longitude) = *location
+                && !((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+            {
+                errors.push(ConfigError::InvalidCoordinates(
+                    setting, latitude, longitude,
+                ));
+            }
+        }
+
+        if let Some(timezone) = &self.timezone
+            && timezone != "auto"
+            && timezone.parse::<chrono_tz::Tz>().is_err()
+        {
+            errors.push(ConfigError::UnknownTimezone(timezone.clone()));
+        }
+
+        if let Some(pattern) = &self.time_format_string
+            && !valid_time_format(pattern)
+        {
+            errors.push(ConfigError::InvalidTimeFormatString(pattern.clone()));
+        }
+
+        if self.caching_duration <= Duration::zero() {
+            errors.push(ConfigError::ZeroCachingDuration);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Replaces `location` with the one of the named profile.
+    pub fn select_profile(&mut self, name: &str) -> Result<(), String> {
+        match self.locations.get(name) {
+            Some(profile) => {
+                self.location = Some(profile.location.clone());
+                self.location_origin = Some(format!("from profile '{}'", name));
+                Ok(())
+            }
+            None if self.locations.is_empty() => Err(format!(
+                "Unknown profile '{}', no [locations] are configured.",
+                name
+            )),
+            None => Err(format!(
+                "Unknown profile '{}', expected one of {}.",
+                name,
+                self.locations
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Settles on a location: the configured `location` (after
+    /// [`Config::apply_env`], [`Config::select_profile`] and any command-line
+    /// override), gpsd when `location_source = "gpsd"`, and finally IP
+    /// geolocation unless `allow_ip_geolocation` is off, which fails only when
+    /// none of the services answer. Geolocated coordinates are reused for
+    /// `location_cache_duration`.
+    pub fn resolve_location(&mut self) -> Result<(), LocationError> {
+        self.resolve(true)
+    }
+
+    /// Like [`Config::resolve_location`], but without reading or writing the
+    /// cached geolocation, for `--dry-run`.
+    pub fn resolve_location_uncached(&mut self) -> Result<(), LocationError> {
+        self.resolve(false)
+    }
+
+    fn resolve(&mut self, cached: bool) -> Result<(), LocationError> {
+        if let Some(location) = &self.location {
+            self.log(&format!(
+                "Location {} {}",
+                location,
+                self.location_origin.as_deref().unwrap_or("from the config")
+            ));
+            return Ok(());
+        }
+
+        if matches!(self.location_source, ConfigLocationSource::Gpsd) {
+            match gpsd::position() {
+                Ok((latitude, longitude)) => {
+                    self.log(&format!("Location {},{} from gpsd", latitude, longitude));
+                    self.location = Some(ConfigLocation::Coordinates(latitude, longitude));
+                    return Ok(());
+                }
+                Err(err) => eprintln!("Failed to read location from gpsd. {}", err),
+            }
+        }
+
+        if !self.allow_ip_geolocation {
+            return Err(LocationError::GeolocationDisabled);
+        }
+
+        if cached && let Some((latitude, longitude)) = caching::load_location(self) {
+            self.log(&format!(
+                "Location {},{} from the cached IP geolocation",
+                latitude, longitude
+            ));
+            self.location = Some(ConfigLocation::Coordinates(latitude, longitude));
+            return Ok(());
+        }
+
+        let mut failures = Vec::new();
+
+        for (service, geolocate) in GEOLOCATION_SERVICES {
+            match geolocate(self) {
+                Ok((latitude, longitude)) => {
+                    self.log(&format!(
+                        "Location {},{} from {}",
+                        latitude, longitude, service
+                    ));
+                    if cached {
+                        caching::save_location(self, latitude, longitude);
+                    }
+                    self.location = Some(ConfigLocation::Coordinates(latitude, longitude));
+                    return Ok(());
+                }
+                Err(reason) => failures.push((service, reason)),
+            }
+        }
+
+        Err(LocationError::ServicesFailed(failures))
+    }
+
+    /// Names the place at the coordinates, e.g. `Berlin, Germany`, or
+    /// [`NO_PLACE`] out at sea. The name is cached alongside the geolocated
+    /// coordinates, so it's only looked up again once those change; open
+    /// water isn't, as an empty answer may as well be a passing glitch.
+    pub fn place_name(&self, latitude: f32, longitude: f32) -> Result<String, String> {
+        if let Some(place) = caching::load_place(self, latitude, longitude) {
+            return Ok(place);
+        }
+
+        let res: ReverseGeocodeResponse = self
+            .get(&format!(
+                "https://api.bigdatacloud.net/data/reverse-geocode-client?latitude={}&longitude={}&localityLanguage=en",
+                latitude, longitude
+            ))
+            .and_then(|res| res.json())
+            .map_err(|err| err.to_string())?;
+
+        let city = if res.city.is_empty() {
+            res.locality
+        } else {
+            res.city
+        };
+        // BigDataCloud only names the areas containing the point, and a
+        // country's area reaches out to its territorial waters. So no country
+        // at all means open sea, with no distance to a match to weigh.
+        let place = match (city.is_empty(), res.country_name.is_empty()) {
+            (false, false) => format!("{}, {}", city, res.country_name),
+            (false, true) => city,
+            (true, false) => res.country_name,
+            (true, true) => NO_PLACE.to_string(),
+        };
+
+        if place != NO_PLACE {
+            caching::save_place(self, latitude, longitude, &place);
+        }
+
+        Ok(place)
+    }
+
+    /// `timezone` as a zone from the bundled database, unless it's unset,
+    /// `auto` or unknown.
+    pub fn named_timezone(&self) -> Option<chrono_tz::Tz> {
+        self.timezone
+            .as_deref()
+            .filter(|&timezone| timezone != "auto")
+            .and_then(|timezone| timezone.parse().ok())
+    }
+
+    /// `time` in the zone it's shown in: the location's, given as its UTC
+    /// offset in seconds, for `timezone = "auto"`, the named `timezone`, or
+    /// otherwise the local one.
+    pub fn localize(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+        utc_offset: Option<i32>,
+    ) -> chrono::DateTime<chrono::FixedOffset> {
+        if self.timezone.as_deref() == Some("auto")
+            && let Some(offset) = utc_offset.and_then(chrono::FixedOffset::east_opt)
+        {
+            return time.with_timezone(&offset);
+        }
+
+        match self.named_timezone() {
+            Some(timezone) => time.with_timezone(&timezone).fixed_offset(),
+            None => time.with_timezone(&chrono::Local).fixed_offset(),
+        }
+    }
+
+    /// Formats `time` with `time_format_string`, or the `time_format` preset
+    /// if no pattern is set or it isn't a valid strftime pattern, which
+    /// [`Config::validate`] reports.
+    pub fn format_time<Tz: chrono::TimeZone>(&self, time: chrono::DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        // Formatting an invalid pattern panics, so check it up front.
+        if let Some(pattern) = &self.time_format_string
+            && valid_time_format(pattern)
+        {
+            return time.format(pattern).to_string();
+        }
+
+        let twelve_hour = match self.time_format {
+            ConfigTimeFormat::_24H => false,
+            ConfigTimeFormat::_12H => true,
+            ConfigTimeFormat::Auto => ConfigTimeFormat::locale_uses_12h(),
+        };
+
+        if twelve_hour {
+            time.format("%I:%M %p").to_string()
+        } else {
+            time.format("%H:%M").to_string()
+        }
+    }
+
+    /// The providers to try in order: `providers` if given, otherwise just `provider`.
+    pub fn provider_chain(&self) -> Vec<ConfigWeatherProvider> {
+        if self.providers.is_empty() {
+            vec![self.provider]
+        } else {
+            self.providers.clone()
+        }
+    }
+
+    /// Formats precipitation given in mm, e.g. `2.3mm` or `0.09in`.
+    pub fn format_precipitation(&self, mm: f32) -> String {
+        match self.units {
+            ConfigUnits::Metric => format!("{:.1}mm", mm),
+            ConfigUnits::Imperial => format!("{:.2}in", mm / 25.4),
+        }
+    }
+
+    /// Formats a snow height given in cm, e.g. `1.5cm` or `0.6in`.
+    pub fn format_snow(&self, cm: f32) -> String {
+        match self.units {
+            ConfigUnits::Metric => format!("{:.1}cm", cm),
+            ConfigUnits::Imperial => format!("{:.1}in", cm / 2.54),
+        }
+    }
+
+    /// Formats a visibility given in meters, e.g. `24.1km` or `15.0mi`.
+    pub fn format_visibility(&self, meters: f32) -> String {
+        match self.units {
+            ConfigUnits::Metric => format!("{:.1}km", meters / 1000.0),
+            ConfigUnits::Imperial => format!("{:.1}mi", meters / 1609.344),
+        }
+    }
+
+    /// `temperature_unit` if set, otherwise °C or °F depending on `units`.
+    pub fn temperature_unit(&self) -> ConfigTemperatureUnit {
+        self.temperature_unit.unwrap_or(match self.units {
+            ConfigUnits::Metric => ConfigTemperatureUnit::Celsius,
+            ConfigUnits::Imperial => ConfigTemperatureUnit::Fahrenheit,
+        })
+    }
+
+    /// `wind_unit` if set, otherwise km/h or mph depending on `units`.
+    pub fn wind_unit(&self) -> ConfigWindUnit {
+        self.wind_unit.unwrap_or(match self.units {
+            ConfigUnits::Metric => ConfigWindUnit::Kmh,
+            ConfigUnits::Imperial => ConfigWindUnit::Mph,
+        })
+    }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// The HTTP client used for every request, geocoding and weather alike.
+    /// It's built once, so `user_agent` and `request_timeout` have to be
+    /// settled before the first request.
+    pub fn client(&self) -> Result<blocking::Client, reqwest::Error> {
+        if let Some(client) = self.client.get() {
+            return Ok(client.clone());
+        }
+
+        let client = blocking::Client::builder()
+            .user_agent(self.user_agent())
+            .timeout(self.request_timeout.to_std().unwrap_or_default())
+            .build()?;
+
+        Ok(self.client.get_or_init(|| client).clone())
+    }
+
+    /// Sends a GET request, retrying connection failures and timeouts up to
+    /// `max_retries` times. The wait starts at `retry_delay` and doubles after
+    /// each attempt. Anything the server answered, even an error status, is
+    /// returned as is.
+    pub fn get(&self, url: &str) -> Result<blocking::Response, reqwest::Error> {
+        let client = self.client()?;
+        let mut delay = self.retry_delay.to_std().unwrap_or_default();
+        let mut attempt = 0;
+
+        self.log(&format!("GET {}", redact(url)));
+
+        loop {
+            match client.get(url).send() {
+                Err(err)
+                    if (err.is_connect() || err.is_timeout()) && attempt < self.max_retries =>
+                {
+                    self.log(&format!("{}, retrying in {:?}", err.without_url(), delay));
+                    thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => {
+                    if let Ok(res) = &result {
+                        self.log(&format!("{} {}", res.status(), redact(url)));
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Prints a diagnostic on stderr if `--verbose` was given.
+    pub fn log(&self, message: &str) {
+        if self.verbose {
+            eprintln!("[verbose] {}", message);
+        }
+    }
+
+    /// The async HTTP client, built once like [`Config::client`].
+    #[cfg(feature = "async")]
+    pub fn async_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        if let Some(client) = self.async_client.get() {
+            return Ok(client.clone());
+        }
+
+        let client = reqwest::Client::builder()
+            .user_agent(self.user_agent())
+            .timeout(self.request_timeout.to_std().unwrap_or_default())
+            .build()?;
+
+        Ok(self.async_client.get_or_init(|| client).clone())
+    }
+
+    /// [`Config::get`] for the async client, retrying and logging the same way.
+    #[cfg(feature = "async")]
+    pub async fn get_async(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
+        let client = self.async_client()?;
+        let mut delay = self.retry_delay.to_std().unwrap_or_default();
+        let mut attempt = 0;
+
+        self.log(&format!("GET {}", redact(url)));
+
+        loop {
+            match client.get(url).send().await {
+                Err(err)
+                    if (err.is_connect() || err.is_timeout()) && attempt < self.max_retries =>
+                {
+                    self.log(&format!("{}, retrying in {:?}", err.without_url(), delay));
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => {
+                    if let Ok(res) = &result {
+                        self.log(&format!("{} {}", res.status(), redact(url)));
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// The API key from `WEATHER_CLI_API_KEY`, `api_key_file`, the OS keyring
+    /// under `api_key_keyring` or `api_key`, whichever comes first.
+    pub fn api_key(&self) -> Option<String> {
+        if let Some(key) = env_api_key() {
+            return Some(key);
+        }
+        if let Some(path) = &self.api_key_file {
+            match fs::read_to_string(path) {
+                Ok(key) => return Some(key.trim().to_string()),
+                Err(err) => eprintln!("Failed to read API key from {}. {}", path.display(), err),
+            }
+        }
+        if let Some(reference) = &self.api_key_keyring {
+            match secrets::get(reference) {
+                Ok(key) => return Some(key),
+                Err(err) => eprintln!("Failed to read API key from keyring. {}", err),
+            }
+        }
+
+        self.api_key.clone()
+    }
+}
+
+/// The place name for coordinates the reverse geocoder knows no name for,
+/// which are mostly at sea.
+pub const NO_PLACE: &str = "open water — no named location";
+
+/// `WEATHER_CLI_API_KEY`, unless it's unset or empty.
+fn env_api_key() -> Option<String> {
+    env::var(API_KEY_VAR).ok().filter(|key| !key.is_empty())
+}
+
+/// Parses the CSV form of `locations_file`, skipping blank lines and `#`
+/// comments. Errors carry the 1-based line number.
+fn parse_locations_csv(content: &str) -> Result<BTreeMap<String, ConfigProfile>, (usize, String)> {
+    let mut locations = BTreeMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, location)) = line.split_once(',') else {
+            return Err((
+                index + 1,
+                format!("expected 'name,location', got '{}'", line),
+            ));
+        };
+        let location = location.parse().map_err(|err| (index + 1, err))?;
+
+        locations.insert(name.trim().to_string(), ConfigProfile { location });
+    }
+
+    Ok(locations)
+}
+
+impl ConfigTimeFormat {
+    /// Locales whose clocks conventionally show 12 hours.
+    const TWELVE_HOUR_LOCALES: [&str; 10] = [
+        "en_US", "en_CA", "en_AU", "en_NZ", "en_PH", "en_IN", "hi_IN", "ur_PK", "ar_EG", "ar_SA",
+    ];
+
+    /// Reads the locale the way libc does, `LC_ALL` before `LC_TIME` before
+    /// `LANG`, and falls back to 24h for unknown or unset locales.
+    fn locale_uses_12h() -> bool {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty());
+        // Drop the encoding and modifier, e.g. `en_US.UTF-8@euro`.
+        let locale = locale
+            .as_deref()
+            .and_then(|locale| locale.split(['.', '@']).next())
+            .unwrap_or_default();
+
+        Self::TWELVE_HOUR_LOCALES.contains(&locale)
+    }
+}
+
+impl ConfigTemperatureUnit {
+    /// The unit to ask Open-Meteo for. It has no Kelvin, so that's requested
+    /// in °C and converted.
+    fn open_meteo(&self) -> ConfigTemperatureUnit {
+        match self {
+            ConfigTemperatureUnit::Fahrenheit => ConfigTemperatureUnit::Fahrenheit,
+            _ => ConfigTemperatureUnit::Celsius,
+        }
+    }
+
+    /// OpenWeatherMap's `units`, which also decides the wind speed unit.
+    fn open_weather_map(&self) -> &'static str {
+        match self {
+            ConfigTemperatureUnit::Celsius => "metric",
+            ConfigTemperatureUnit::Fahrenheit => "imperial",
+            ConfigTemperatureUnit::Kelvin => "standard",
+        }
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            ConfigTemperatureUnit::Celsius => "C",
+            ConfigTemperatureUnit::Fahrenheit => "F",
+            ConfigTemperatureUnit::Kelvin => "K",
+        }
+    }
+
+    pub fn from_celsius(&self, celsius: f32) -> f32 {
+        match self {
+            ConfigTemperatureUnit::Celsius => celsius,
+            ConfigTemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            ConfigTemperatureUnit::Kelvin => celsius + 273.15,
+        }
+    }
+
+    pub fn to_celsius(&self, value: f32) -> f32 {
+        match self {
+            ConfigTemperatureUnit::Celsius => value,
+            ConfigTemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            ConfigTemperatureUnit::Kelvin => value - 273.15,
+        }
+    }
+}
+
+/// The names Open-Meteo's `temperature_unit` expects.
+impl Display for ConfigTemperatureUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigTemperatureUnit::Celsius => write!(f, "celsius"),
+            ConfigTemperatureUnit::Fahrenheit => write!(f, "fahrenheit"),
+            ConfigTemperatureUnit::Kelvin => write!(f, "kelvin"),
+        }
+    }
+}
+
+impl ConfigWindArrow {
+    pub fn arrow(&self, degree: i16) -> &'static str {
+        const ARROWS: [&str; 8] = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+
+        let degree = match self {
+            ConfigWindArrow::From => degree as i32,
+            ConfigWindArrow::To => degree as i32 + 180,
+        };
+
+        ARROWS[((degree.rem_euclid(360) as f32 + 22.5) / 45.0) as usize % 8]
+    }
+}
+
+impl WeatherCondition {
+    pub fn is_snowy(&self) -> bool {
+        matches!(
+            self,
+            WeatherCondition::Snowy | WeatherCondition::SnowGrains | WeatherCondition::SnowShowers
+        )
+    }
+
+    /// The emoji for the condition, with a moon instead of the sun at night.
+    pub fn icon(&self, is_day: bool) -> &'static str {
+        match self {
+            WeatherCondition::Clear if is_day => "☀️",
+            WeatherCondition::Clear => "🌙",
+            WeatherCondition::PartlyCloudy if is_day => "⛅",
+            WeatherCondition::PartlyCloudy => "☁️",
+            WeatherCondition::Overcast => "☁️",
+            WeatherCondition::Foggy => "🌫️",
+            WeatherCondition::Drizzle => "🌦️",
+            WeatherCondition::Rainy => "🌧️",
+            WeatherCondition::Snowy => "🌨️",
+            WeatherCondition::SnowGrains => "🌨️",
+            WeatherCondition::RainShowers => "🌦️",
+            WeatherCondition::SnowShowers => "🌨️",
+            WeatherCondition::Thunderstorms => "⛈️",
+            WeatherCondition::Unknown => "❔",
+        }
+    }
+
+    /// Human-readable description of a provider's raw condition code: WMO weather
+    /// interpretation codes for Open-Meteo, condition ids for OpenWeatherMap,
+    /// condition codes for WeatherAPI.com and WorldWeatherOnline codes for wttr.in.
+    pub fn describe_code(provider: &ConfigWeatherProvider, code: i32) -> Option<&'static str> {
+        let description = match provider {
+            ConfigWeatherProvider::OpenMeteo => match code {
+                0 => "Clear sky",
+                1 => "Mainly clear",
+                2 => "Partly cloudy",
+                3 => "Overcast",
+                45 => "Fog",
+                48 => "Depositing rime fog",
+                51 => "Light drizzle",
+                53 => "Moderate drizzle",
+                55 => "Dense drizzle",
+                56 => "Light freezing drizzle",
+                57 => "Dense freezing drizzle",
+                61 => "Slight rain",
+                63 => "Moderate rain",
+                65 => "Heavy rain",
+                66 => "Light freezing rain",
+                67 => "Heavy freezing rain",
+                71 => "Slight snow fall",
+                73 => "Moderate snow fall",
+                75 => "Heavy snow fall",
+                77 => "Snow grains",
+                80 => "Slight rain showers",
+                81 => "Moderate rain showers",
+                82 => "Violent rain showers",
+                85 => "Slight snow showers",
+                86 => "Heavy snow showers",
+                95 => "Thunderstorm",
+                96 => "Thunderstorm with slight hail",
+                99 => "Thunderstorm with heavy hail",
+                _ => return None,
+            },
+            ConfigWeatherProvider::OpenWeatherMap => match code {
+                200 => "Thunderstorm with light rain",
+                201 => "Thunderstorm with rain",
+                202 => "Thunderstorm with heavy rain",
+                210 => "Light thunderstorm",
+                211 => "Thunderstorm",
+                212 => "Heavy thunderstorm",
+                221 => "Ragged thunderstorm",
+                230 => "Thunderstorm with light drizzle",
+                231 => "Thunderstorm with drizzle",
+                232 => "Thunderstorm with heavy drizzle",
+                300 => "Light intensity drizzle",
+                301 => "Drizzle",
+                302 => "Heavy intensity drizzle",
+                310 => "Light intensity drizzle rain",
+                311 => "Drizzle rain",
+                312 => "Heavy intensity drizzle rain",
+                313 => "Shower rain and drizzle",
+                314 => "Heavy shower rain and drizzle",
+                321 => "Shower drizzle",
+                500 => "Light rain",
+                501 => "Moderate rain",
+                502 => "Heavy intensity rain",
+                503 => "Very heavy rain",
+                504 => "Extreme rain",
+                511 => "Freezing rain",
+                520 => "Light intensity shower rain",
+                521 => "Shower rain",
+                522 => "Heavy intensity shower rain",
+                531 => "Ragged shower rain",
+                600 => "Light snow",
+                601 => "Snow",
+                602 => "Heavy snow",
+                611 => "Sleet",
+                612 => "Light shower sleet",
+                613 => "Shower sleet",
+                615 => "Light rain and snow",
+                616 => "Rain and snow",
+                620 => "Light shower snow",
+                621 => "Shower snow",
+                622 => "Heavy shower snow",
+                701 => "Mist",
+                711 => "Smoke",
+                721 => "Haze",
+                731 => "Sand/dust whirls",
+                741 => "Fog",
+                751 => "Sand",
+                761 => "Dust",
+                762 => "Volcanic ash",
+                771 => "Squalls",
+                781 => "Tornado",
+                800 => "Clear sky",
+                801 => "Few clouds",
+                802 => "Scattered clouds",
+                803 => "Broken clouds",
+                804 => "Overcast clouds",
+                _ => return None,
+            },
+            ConfigWeatherProvider::WeatherApi => match code {
+                1000 => "Sunny",
+                1003 => "Partly cloudy",
+                1006 => "Cloudy",
+                1009 => "Overcast",
+                1030 => "Mist",
+                1063 => "Patchy rain possible",
+                1066 => "Patchy snow possible",
+                1069 => "Patchy sleet possible",
+                1072 => "Patchy freezing drizzle possible",
+                1087 => "Thundery outbreaks possible",
+                1114 => "Blowing snow",
+                1117 => "Blizzard",
+                1135 => "Fog",
+                1147 => "Freezing fog",
+                1150 => "Patchy light drizzle",
+                1153 => "Light drizzle",
+                1168 => "Freezing drizzle",
+                1171 => "Heavy freezing drizzle",
+                1180 => "Patchy light rain",
+                1183 => "Light rain",
+                1186 => "Moderate rain at times",
+                1189 => "Moderate rain",
+                1192 => "Heavy rain at times",
+                1195 => "Heavy rain",
+                1198 => "Light freezing rain",
+                1201 => "Moderate or heavy freezing rain",
+                1204 => "Light sleet",
+                1207 => "Moderate or heavy sleet",
+                1210 => "Patchy light snow",
+                1213 => "Light snow",
+                1216 => "Patchy moderate snow",
+                1219 => "Moderate snow",
+                1222 => "Patchy heavy snow",
+                1225 => "Heavy snow",
+                1237 => "Ice pellets",
+                1240 => "Light rain shower",
+                1243 => "Moderate or heavy rain shower",
+                1246 => "Torrential rain shower",
+                1249 => "Light sleet showers",
+                1252 => "Moderate or heavy sleet showers",
+                1255 => "Light snow showers",
+                1258 => "Moderate or heavy snow showers",
+                1261 => "Light showers of ice pellets",
+                1264 => "Moderate or heavy showers of ice pellets",
+                1273 => "Patchy light rain with thunder",
+                1276 => "Moderate or heavy rain with thunder",
+                1279 => "Patchy light snow with thunder",
+                1282 => "Moderate or heavy snow with thunder",
+                _ => return None,
+            },
+            ConfigWeatherProvider::Wttr => match code {
+                113 => "Clear/Sunny",
+                116 => "Partly cloudy",
+                119 => "Cloudy",
+                122 => "Overcast",
+                143 => "Mist",
+                176 => "Patchy rain possible",
+                179 => "Patchy snow possible",
+                182 => "Patchy sleet possible",
+                185 => "Patchy freezing drizzle possible",
+                200 => "Thundery outbreaks possible",
+                227 => "Blowing snow",
+                230 => "Blizzard",
+                248 => "Fog",
+                260 => "Freezing fog",
+                263 => "Patchy light drizzle",
+                266 => "Light drizzle",
+                281 => "Freezing drizzle",
+                284 => "Heavy freezing drizzle",
+                293 => "Patchy light rain",
+                296 => "Light rain",
+                299 => "Moderate rain at times",
+                302 => "Moderate rain",
+                305 => "Heavy rain at times",
+                308 => "Heavy rain",
+                311 => "Light freezing rain",
+                314 => "Moderate or heavy freezing rain",
+                317 => "Light sleet",
+                320 => "Moderate or heavy sleet",
+                323 => "Patchy light snow",
+                326 => "Light snow",
+                329 => "Patchy moderate snow",
+                332 => "Moderate snow",
+                335 => "Patchy heavy snow",
+                338 => "Heavy snow",
+                350 => "Ice pellets",
+                353 => "Light rain shower",
+                356 => "Moderate or heavy rain shower",
+                359 => "Torrential rain shower",
+                362 => "Light sleet showers",
+                365 => "Moderate or heavy sleet showers",
+                368 => "Light snow showers",
+                371 => "Moderate or heavy snow showers",
+                374 => "Light showers of ice pellets",
+                377 => "Moderate or heavy showers of ice pellets",
+                386 => "Patchy light rain with thunder",
+                389 => "Moderate or heavy rain with thunder",
+                392 => "Patchy light snow with thunder",
+                395 => "Moderate or heavy snow with thunder",
+                _ => return None,
+            },
+            // Met.no and the NWS describe the weather in text rather than numbers.
+            ConfigWeatherProvider::MetNo | ConfigWeatherProvider::NationalWeatherService => {
+                return None;
+            }
+        };
+
+        Some(description)
+    }
+}
+
+impl ConfigWindUnit {
+    /// Open-Meteo's `wind_speed_unit`. It has no Beaufort scale, so that's
+    /// requested in km/h and converted.
+    fn open_meteo(&self) -> &'static str {
+        match self {
+            ConfigWindUnit::Kmh | ConfigWindUnit::Beaufort => "kmh",
+            ConfigWindUnit::Mph => "mph",
+            ConfigWindUnit::Ms => "ms",
+            ConfigWindUnit::Knots => "kn",
+        }
+    }
+
+    pub fn from_kmh(&self, kmh: f32) -> f32 {
+        match self {
+            ConfigWindUnit::Kmh => kmh,
+            ConfigWindUnit::Mph => kmh / 1.609_344,
+            ConfigWindUnit::Ms => kmh / 3.6,
+            ConfigWindUnit::Knots => kmh / 1.852,
+            ConfigWindUnit::Beaufort => ((kmh / 3.6 / 0.836).powf(2.0 / 3.0).round()).min(12.0),
+        }
+    }
+
+    /// Formats a speed that's already in this unit.
+    pub fn format(&self, value: f32) -> String {
+        match self {
+            ConfigWindUnit::Kmh => format!("{:.1}km/h", value),
+            ConfigWindUnit::Mph => format!("{:.1}mph", value),
+            ConfigWindUnit::Ms => format!("{:.1}m/s", value),
+            ConfigWindUnit::Knots => format!("{:.1}kn", value),
+            ConfigWindUnit::Beaufort => format!("{:.0}Bft", value),
+        }
+    }
+}
+
+impl ConfigPressureUnit {
+    pub fn from_hpa(&self, hpa: f32) -> f32 {
+        match self {
+            ConfigPressureUnit::HPa => hpa,
+            ConfigPressureUnit::InHg => hpa * 0.029_53,
+            ConfigPressureUnit::MmHg => hpa * 0.750_06,
+            ConfigPressureUnit::KPa => hpa / 10.0,
+        }
+    }
+
+    pub fn format(&self, hpa: f32) -> String {
+        let value = self.from_hpa(hpa);
+
+        match self {
+            ConfigPressureUnit::HPa => format!("{:.0}hPa", value),
+            ConfigPressureUnit::InHg => format!("{:.2}inHg", value),
+            ConfigPressureUnit::MmHg => format!("{:.0}mmHg", value),
+            ConfigPressureUnit::KPa => format!("{:.1}kPa", value),
+        }
+    }
+}
+
+impl TryFrom<u8> for ConfigCompassPoints {
+    type Error = String;
+
+    fn try_from(points: u8) -> Result<Self, Self::Error> {
+        match points {
+            4 => Ok(ConfigCompassPoints::Four),
+            8 => Ok(ConfigCompassPoints::Eight),
+            16 => Ok(ConfigCompassPoints::Sixteen),
+            _ => Err(format!(
+                "expected 4, 8 or 16 compass points, got {}",
+                points
+            )),
+        }
+    }
+}
+
+impl From<ConfigCompassPoints> for u8 {
+    fn from(points: ConfigCompassPoints) -> Self {
+        match points {
+            ConfigCompassPoints::Four => 4,
+            ConfigCompassPoints::Eight => 8,
+            ConfigCompassPoints::Sixteen => 16,
+        }
+    }
+}
+
+impl ConfigTemperatureThresholds {
+    pub fn icon(&self, temperature: f32, icons: IconSet) -> &'static str {
+        let (cold, mild, hot) = match icons {
+            IconSet::Emoji => ("🥶", "🌡️", "🥵"),
+            IconSet::Ascii => ("cold", "mild", "hot"),
+        };
+
+        if temperature <= self.cold {
+            cold
+        } else if temperature >= self.hot {
+            hot
+        } else {
+            mild
+        }
+    }
+}
+
+impl FromStr for ConfigWeatherProvider {
+    type Err = String;
+
+    /// Goes through the serde names, so the accepted values match the config's.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(s.into_deserializer())
+            .map_err(|err: serde::de::value::Error| err.to_string())
+    }
+}
+
+impl FromStr for ConfigField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(s.into_deserializer())
+            .map_err(|err: serde::de::value::Error| err.to_string())
+    }
+}
+
+impl FromStr for ConfigUnits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "metric" => Ok(ConfigUnits::Metric),
+            "imperial" => Ok(ConfigUnits::Imperial),
+            _ => Err(format!("expected metric or imperial, got '{}'", s)),
+        }
+    }
+}
+
+impl FromStr for ConfigColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(ConfigColor::Always),
+            "auto" => Ok(ConfigColor::Auto),
+            "never" => Ok(ConfigColor::Never),
+            _ => Err(format!("expected always, auto or never, got '{}'", s)),
+        }
+    }
+}
+
+impl ConfigColor {
+    pub fn enabled(&self) -> bool {
+        match self {
+            ConfigColor::Always => true,
+            ConfigColor::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ConfigColor::Never => false,
+        }
+    }
+}
+
+impl IconSet {
+    /// Guesses whether emoji will render: the Linux console and `dumb`
+    /// terminals can't show them, and neither can a non-UTF-8 locale.
+    pub fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if term == "linux"
+            || term == "dumb"
+            || !(locale.contains("utf-8") || locale.contains("utf8"))
+        {
+            IconSet::Ascii
+        } else {
+            IconSet::Emoji
+        }
+    }
+}
+
+/// Extracts the number in front of a formatted value, e.g. `-3` from `"-3°C"`.
+pub fn numeric_value(value: &str) -> Option<f32> {
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && c == '-')))
+        .map_or(value.len(), |(i, _)| i);
+
+    value[..end].parse().ok()
+}
+
+/// Converts a formatted wind speed such as `"12.3km/h"` or `"8mph"` to km/h.
+/// Beaufort values become the speed in the middle of their band.
+pub fn kilometers_per_hour(value: &str) -> Option<f32> {
+    let number = numeric_value(value)?;
+    let unit = value
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+        .trim();
+
+    match unit {
+        "mph" => Some(number * 1.609_344),
+        "m/s" => Some(number * 3.6),
+        "kn" => Some(number * 1.852),
+        "Bft" => Some(0.836 * number.powf(1.5) * 3.6),
+        _ => Some(number),
+    }
+}
+
+/// Parses durations such as `"2d"`, `"1h"`, `"15min"`, `"10s"` or compound
+/// ones like `"1h30min"`, summing the parts. Durations too long to represent
+/// are rejected.
+pub fn parse_duration(string: &str) -> Option<Duration> {
+    let mut rest = string.trim();
+    let mut total = Duration::zero();
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+
+        let units = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..units] {
+            "d" => Duration::try_days(amount),
+            "h" => Duration::try_hours(amount),
+            "min" => Duration::try_minutes(amount),
+            "s" => Duration::try_seconds(amount),
+            _ => return None,
+        };
+        // Too long for a `Duration` is as invalid as a unit that doesn't exist.
+        total = total.checked_add(&part?)?;
+        rest = &rest[units..];
+    }
+
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressure_conversion() {
+        let standard = 1013.25;
+
+        assert!((ConfigPressureUnit::InHg.from_hpa(standard) - 29.92).abs() < 0.01);
+        assert!((ConfigPressureUnit::MmHg.from_hpa(standard) - 760.0).abs() < 0.1);
+        assert_eq!(ConfigPressureUnit::KPa.from_hpa(standard), 101.325);
+        assert_eq!(ConfigPressureUnit::HPa.from_hpa(standard), standard);
+
+        assert_eq!(ConfigPressureUnit::HPa.format(standard), "1013hPa");
+        assert_eq!(ConfigPressureUnit::InHg.format(standard), "29.92inHg");
+        assert_eq!(ConfigPressureUnit::MmHg.format(standard), "760mmHg");
+        assert_eq!(ConfigPressureUnit::KPa.format(standard), "101.3kPa");
+    }
+
+    #[test]
+    fn describe_code_covers_the_wmo_table() {
+        let wmo = [
+            0, 1, 2, 3, 45, 48, 51, 53, 55, 56, 57, 61, 63, 65, 66, 67, 71, 73, 75, 77, 80, 81, 82,
+            85, 86, 95, 96, 99,
+        ];
+
+        for code in 0..100 {
+            let description =
+                WeatherCondition::describe_code(&ConfigWeatherProvider::OpenMeteo, code);
+
+            assert_eq!(
+                description.is_some(),
+                wmo.contains(&code),
+                "WMO code {}",
+                code
+            );
+        }
+    }
+
+    /// Every code in a provider's documented table, with the range it's
+    /// swept over to check nothing else is described.
+    #[test]
+    fn describe_code_covers_the_provider_tables() {
+        use ConfigWeatherProvider::*;
+
+        let owm = [
+            200, 201, 202, 210, 211, 212, 221, 230, 231, 232, 300, 301, 302, 310, 311, 312, 313,
+            314, 321, 500, 501, 502, 503, 504, 511, 520, 521, 522, 531, 600, 601, 602, 611, 612,
+            613, 615, 616, 620, 621, 622, 701, 711, 721, 731, 741, 751, 761, 762, 771, 781, 800,
+            801, 802, 803, 804,
+        ];
+        let weather_api = [
+            1000, 1003, 1006, 1009, 1030, 1063, 1066, 1069, 1072, 1087, 1114, 1117, 1135, 1147,
+            1150, 1153, 1168, 1171, 1180, 1183, 1186, 1189, 1192, 1195, 1198, 1201, 1204, 1207,
+            1210, 1213, 1216, 1219, 1222, 1225, 1237, 1240, 1243, 1246, 1249, 1252, 1255, 1258,
+            1261, 1264, 1273, 1276, 1279, 1282,
+        ];
+        let wttr = [
+            113, 116, 119, 122, 143, 176, 179, 182, 185, 200, 227, 230, 248, 260, 263, 266, 281,
+            284, 293, 296, 299, 302, 305, 308, 311, 314, 317, 320, 323, 326, 329, 332, 335, 338,
+            350, 353, 356, 359, 362, 365, 368, 371, 374, 377, 386, 389, 392, 395,
+        ];
+        let tables: [(_, &[i32], _); 3] = [
+            (OpenWeatherMap, &owm, 0..1000),
+            (WeatherApi, &weather_api, 900..1400),
+            (Wttr, &wttr, 0..500),
+        ];
+
+        for (provider, table, range) in tables {
+            for code in range {
+                let description = WeatherCondition::describe_code(&provider, code);
+
+                assert_eq!(
+                    description.is_some(),
+                    table.contains(&code),
+                    "{} code {}",
+                    provider,
+                    code
+                );
+                if let Some(description) = description {
+                    assert!(
+                        !description.is_empty() && description != "Unknown",
+                        "{} code {}",
+                        provider,
+                        code
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn describe_code_ranges() {
+        use ConfigWeatherProvider::*;
+
+        let cases = [
+            (OpenMeteo, 0, Some("Clear sky")),
+            (OpenMeteo, 99, Some("Thunderstorm with heavy hail")),
+            (OpenMeteo, -1, None),
+            (OpenMeteo, 100, None),
+            (OpenWeatherMap, 200, Some("Thunderstorm with light rain")),
+            (OpenWeatherMap, 321, Some("Shower drizzle")),
+            (OpenWeatherMap, 511, Some("Freezing rain")),
+            (OpenWeatherMap, 622, Some("Heavy shower snow")),
+            (OpenWeatherMap, 781, Some("Tornado")),
+            (OpenWeatherMap, 804, Some("Overcast clouds")),
+            (OpenWeatherMap, 805, None),
+            (OpenWeatherMap, 0, None),
+            (WeatherApi, 1000, Some("Sunny")),
+            (
+                WeatherApi,
+                1282,
+                Some("Moderate or heavy snow with thunder"),
+            ),
+            (WeatherApi, 1001, None),
+            (Wttr, 113, Some("Clear/Sunny")),
+            (Wttr, 395, Some("Moderate or heavy snow with thunder")),
+            (Wttr, 114, None),
+            (MetNo, 0, None),
+            (NationalWeatherService, 0, None),
+        ];
+
+        for (provider, code, description) in cases {
+            assert_eq!(
+                WeatherCondition::describe_code(&provider, code),
+                description,
+                "{} {}",
+                provider,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn time_format_string_patterns() {
+        use chrono::TimeZone;
+
+        let time = chrono::FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 14, 15, 9, 0)
+            .unwrap();
+        let config = |pattern: Option<&str>, time_format| Config {
+            time_format,
+            time_format_string: pattern.map(str::to_string),
+            ..Config::default()
+        };
+
+        let cases = [
+            (Some("%a %H:%M"), "Thu 15:09"),
+            (Some("%d.%m. %H:%M %:z"), "14.03. 15:09 +01:00"),
+            (Some("at noon"), "at noon"),
+            (Some("%Q"), "15:09"),
+            (Some("%H:%"), "15:09"),
+            (Some("%"), "15:09"),
+            (None, "15:09"),
+        ];
+
+        for (pattern, expected) in cases {
+            assert_eq!(
+                config(pattern, ConfigTimeFormat::_24H).format_time(time),
+                expected,
+                "{:?}",
+                pattern
+            );
+        }
+        assert_eq!(
+            config(Some("%Q"), ConfigTimeFormat::_12H).format_time(time),
+            "03:09 PM"
+        );
+
+        for (pattern, valid) in [
+            ("%a %H:%M", true),
+            ("at noon", true),
+            ("%Q", false),
+            ("%", false),
+        ] {
+            let errors = config(Some(pattern), ConfigTimeFormat::_24H)
+                .validate()
+                .err()
+                .unwrap_or_default();
+            let rejected = errors
+                .iter()
+                .any(|err| matches!(err, ConfigError::InvalidTimeFormatString(_)));
+
+            assert_eq!(rejected, !valid, "{:?}", pattern);
+        }
+    }
+
+    #[test]
+    fn wind_arrow_boundaries() {
+        let cases = [
+            (0, "↑", "↓"),
+            (11, "↑", "↓"),
+            (22, "↑", "↓"),
+            (23, "↗", "↙"),
+            (90, "→", "←"),
+            (337, "↖", "↘"),
+            (338, "↑", "↓"),
+            (349, "↑", "↓"),
+            (360, "↑", "↓"),
+        ];
+
+        for (degree, from, to) in cases {
+            assert_eq!(ConfigWindArrow::From.arrow(degree), from, "{}°", degree);
+            assert_eq!(ConfigWindArrow::To.arrow(degree), to, "{}°", degree);
+        }
+    }
+
+    #[test]
+    fn parse_duration_accepts_each_unit() {
+        assert_eq!(parse_duration("2d"), Some(Duration::days(2)));
+        assert_eq!(parse_duration("1h"), Some(Duration::hours(1)));
+        assert_eq!(parse_duration("15min"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration(" 10s "), Some(Duration::seconds(10)));
+        assert_eq!(
+            parse_duration("1d2h30min5s"),
+            Some(
+                Duration::days(1)
+                    + Duration::hours(2)
+                    + Duration::minutes(30)
+                    + Duration::seconds(5)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        for input in ["", "10", "h", "1m", "1 h", "-1h", "1h30"] {
+            assert_eq!(parse_duration(input), None, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parse_duration_rejects_overflow() {
+        assert_eq!(parse_duration("99999999999999d"), None);
+        assert_eq!(parse_duration("9999999999999999d"), None);
+        assert_eq!(parse_duration("99999999999999999999s"), None);
+        assert_eq!(parse_duration("100000000000d100000000000d"), None);
+    }
+
+    #[test]
+    fn duration_format_round_trips() {
+        for input in ["2d", "1h", "15min", "10s", "1h30min", "1d2h3min4s"] {
+            let duration = parse_duration(input).unwrap();
+
+            assert_eq!(duration_format::format(&duration), input);
+            assert_eq!(
+                parse_duration(&duration_format::format(&duration)),
+                Some(duration)
+            );
+        }
+        assert_eq!(duration_format::format(&Duration::minutes(90)), "1h30min");
+        assert_eq!(duration_format::format(&Duration::zero()), "0min");
+    }
+
+    #[test]
+    fn locations_csv_accepts_coordinates_and_cities() {
+        let locations =
+            parse_locations_csv("# name,location\nwork, 53.55, 9.99\n\nhome,Berlin,DE\n").unwrap();
+
+        assert!(matches!(
+            locations["work"].location,
+            ConfigLocation::Coordinates(latitude, longitude) if latitude == 53.55 && longitude == 9.99
+        ));
+        assert!(matches!(
+            &locations["home"].location,
+            ConfigLocation::City(city, country) if city == "Berlin" && country == "DE"
+        ));
+    }
+
+    #[test]
+    fn locations_csv_reports_the_line() {
+        let Err((line, _)) = parse_locations_csv("work,53.55,9.99\n\nhome\n") else {
+            panic!("a line without a location parsed");
+        };
+
+        assert_eq!(line, 3);
+    }
+
+    /// A configured location wins over gpsd and IP geolocation, which are
+    /// never asked.
+    #[test]
+    fn configured_location_is_kept() {
+        let location = ConfigLocation::Coordinates(52.52, 13.4);
+        let mut config = Config {
+            location: Some(location.clone()),
+            location_source: ConfigLocationSource::Gpsd,
+            ..Config::default()
+        };
+
+        assert!(config.resolve_location().is_ok());
+        assert!(config.location == Some(location));
+    }
+
+    #[test]
+    fn no_location_without_geolocation() {
+        let mut config = Config {
+            location: None,
+            allow_ip_geolocation: false,
+            ..Config::default()
+        };
+
+        assert!(matches!(
+            config.resolve_location(),
+            Err(LocationError::GeolocationDisabled)
+        ));
+        assert!(config.location.is_none());
+    }
+
+    fn clear_sky(fields: serde_json::Value) -> WeatherData {
+        let mut reading = serde_json::json!({
+            "temperature": "20°C",
+            "feels_like": "18°C",
+            "wind_speed": "5km/h",
+            "wind_direction": "N",
+            "condition": "Clear",
+        });
+        reading
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+
+        serde_json::from_value(reading).unwrap()
+    }
+
+    #[test]
+    fn night_icons_follow_the_sun() {
+        let now = chrono::Utc::now();
+        let hour = Duration::hours(1);
+        let icon = |weather: WeatherData| weather.condition.icon(weather.is_daytime());
+
+        // Without an is_day flag or sun times, it's taken to be day.
+        assert_eq!(icon(clear_sky(serde_json::json!({}))), "☀️");
+        assert_eq!(
+            icon(clear_sky(serde_json::json!({ "sunrise": now - hour }))),
+            "☀️"
+        );
+
+        let up = serde_json::json!({ "sunrise": now - hour, "sunset": now + hour });
+        let down = serde_json::json!({ "sunrise": now + hour, "sunset": now + hour * 12 });
+        assert_eq!(icon(clear_sky(up)), "☀️");
+        assert_eq!(icon(clear_sky(down)), "🌙");
+
+        // A reported is_day wins over the sun times.
+        let night = serde_json::json!({
+            "is_day": false,
+            "sunrise": now - hour,
+            "sunset": now + hour,
+        });
+        assert_eq!(icon(clear_sky(night)), "🌙");
+
+        assert_eq!(WeatherCondition::PartlyCloudy.icon(true), "⛅");
+        assert_eq!(WeatherCondition::PartlyCloudy.icon(false), "☁️");
+        assert_eq!(WeatherCondition::Rainy.icon(false), "🌧️");
+    }
+}
@@ -1,297 +1,2241 @@
-use crate::providers::{OpenMeteo, WeatherProvider};
-use chrono::Duration;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use dirs::home_dir;
-use reqwest::blocking;
-use serde::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
-use std::fs;
-
-mod caching;
-mod providers;
-
-mod duration_format {
-    use crate::parse_duration;
-    use chrono::Duration;
-    use serde::{Deserializer, Serializer, de};
-    use std::fmt;
-    use std::fmt::Formatter;
-
-    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let hours = duration.num_hours();
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use std::{env, fs, slice, thread};
+use weather_cli::moon::{MoonPhase, moon_phase};
+use weather_cli::providers::{self, OpenMeteo, ProviderError, WeatherProvider};
+use weather_cli::{
+    AirQuality, Config, ConfigCacheMode, ConfigColor, ConfigField, ConfigLocation,
+    ConfigTemperatureThresholds, ConfigUnits, ConfigWeatherProvider, DailyForecast, HourlyForecast,
+    IconSet, LOCATION_VAR, LocationError, ResolvedLocation, WeatherAlert, WeatherCondition,
+    WeatherData, alert, caching, kilometers_per_hour, numeric_value, parse_duration, redact,
+    secrets, timespec,
+};
+
+#[derive(Clone, ValueEnum)]
+enum Output {
+    /// The default human-readable layout
+    Human,
+    /// Fixed, space-aligned columns for scripts
+    Table,
+    /// A JSON object with `text`, `tooltip` and `class` for Waybar or Polybar
+    Waybar,
+}
+
+/// Columns for `--output table`. They are printed in the order given.
+#[derive(Clone, ValueEnum)]
+#[value(rename_all = "snake_case")]
+enum Column {
+    Temp,
+    FeelsLike,
+    Condition,
+    #[value(alias = "wind_speed")]
+    Wind,
+    WindGust,
+    WindDirection,
+    Humidity,
+    DewPoint,
+    Pressure,
+    Visibility,
+    CloudCover,
+    UvIndex,
+    Precipitation,
+    Snowfall,
+    SnowDepth,
+    Sunrise,
+    Sunset,
+    Time,
+    Provider,
+}
+
+/// Every field of the default output, in the order it's printed without
+/// `fields`. The optional ones are left out unless their `show_*` says so.
+const DEFAULT_FIELDS: &[ConfigField] = &[
+    ConfigField::Temperature,
+    ConfigField::FeelsLike,
+    ConfigField::Condition,
+    ConfigField::Wind,
+    ConfigField::Humidity,
+    ConfigField::DewPoint,
+    ConfigField::Pressure,
+    ConfigField::Visibility,
+    ConfigField::CloudCover,
+    ConfigField::UvIndex,
+    ConfigField::Precipitation,
+    ConfigField::Snow,
+    ConfigField::Sun,
+    ConfigField::Moon,
+    ConfigField::Alerts,
+    ConfigField::Location,
+    ConfigField::AirQuality,
+    ConfigField::Time,
+    ConfigField::Provider,
+];
+
+const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Temp,
+    Column::FeelsLike,
+    Column::Condition,
+    Column::Wind,
+    Column::WindDirection,
+    Column::Humidity,
+    Column::Pressure,
+    Column::Time,
+];
 
-        if hours > 0 && duration.num_minutes() % 60 == 0 {
-            serializer.serialize_str(&format!("{}h", hours))
-        } else {
-            let minutes = duration.num_minutes();
+impl Column {
+    fn name(&self) -> &'static str {
+        match self {
+            Column::Temp => "temp",
+            Column::FeelsLike => "feels_like",
+            Column::Condition => "condition",
+            Column::Wind => "wind",
+            Column::WindGust => "wind_gust",
+            Column::WindDirection => "wind_direction",
+            Column::Humidity => "humidity",
+            Column::DewPoint => "dew_point",
+            Column::Pressure => "pressure",
+            Column::Visibility => "visibility",
+            Column::CloudCover => "cloud_cover",
+            Column::UvIndex => "uv_index",
+            Column::Precipitation => "precipitation",
+            Column::Snowfall => "snowfall",
+            Column::SnowDepth => "snow_depth",
+            Column::Sunrise => "sunrise",
+            Column::Sunset => "sunset",
+            Column::Time => "time",
+            Column::Provider => "provider",
+        }
+    }
 
-            serializer.serialize_str(&format!("{}min", minutes))
+    fn value(&self, config: &Config, weather: &WeatherData, current_time: &str) -> String {
+        match self {
+            Column::Temp => weather.temperature.clone(),
+            Column::FeelsLike => weather.feels_like.clone(),
+            Column::Condition => condition(config, weather),
+            Column::Wind => weather.wind_speed.clone(),
+            Column::WindGust => weather.wind_gust.clone().unwrap_or("-".to_string()),
+            Column::WindDirection => weather.wind_direction.clone(),
+            Column::Humidity => weather
+                .humidity
+                .map_or("-".to_string(), |h| format!("{}%", h)),
+            Column::DewPoint => weather.dew_point.clone().unwrap_or("-".to_string()),
+            Column::Pressure => weather
+                .pressure
+                .map_or("-".to_string(), |p| config.pressure_unit.format(p)),
+            Column::Visibility => weather
+                .visibility
+                .map_or("-".to_string(), |m| config.format_visibility(m)),
+            Column::CloudCover => weather
+                .cloud_cover
+                .map_or("-".to_string(), |c| format!("{}%", c)),
+            Column::UvIndex => weather
+                .uv_index
+                .map_or("-".to_string(), |uv| format!("{:.1}", uv)),
+            Column::Precipitation => weather
+                .precipitation
+                .map_or("-".to_string(), |mm| config.format_precipitation(mm)),
+            Column::Snowfall => weather
+                .snowfall
+                .map_or("-".to_string(), |cm| config.format_snow(cm)),
+            Column::SnowDepth => weather
+                .snow_depth
+                .map_or("-".to_string(), |cm| config.format_snow(cm)),
+            Column::Sunrise => weather.sunrise.map_or("-".to_string(), |time| {
+                config.format_time(config.localize(time, weather.utc_offset))
+            }),
+            Column::Sunset => weather.sunset.map_or("-".to_string(), |time| {
+                config.format_time(config.localize(time, weather.utc_offset))
+            }),
+            Column::Time => current_time.to_string(),
+            Column::Provider => config.provider.to_string(),
         }
     }
+}
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct DurationVisitor;
+#[derive(Clone)]
+enum Part {
+    Text(String),
+    Field(Column),
+}
+
+/// A `--format` string such as `"{temp} {condition}"`. Placeholders are the
+/// `--columns` names, `{wind_speed}` also works for `{wind}`, and `{{` and
+/// `}}` stand for literal braces.
+#[derive(Clone)]
+struct Template(Vec<Part>);
 
-        impl de::Visitor<'_> for DurationVisitor {
-            type Value = Duration;
+impl FromStr for Template {
+    type Err = String;
 
-            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-                formatter.write_str("a duration formated as '1h' or '30min'")
-            }
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
 
-            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                parse_duration(value).ok_or_else(|| E::custom("failed to parse duration"))
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let Some((name, rest)) = chars.as_str().split_once('}') else {
+                        return Err(format!("unclosed placeholder in {:?}", template));
+                    };
+                    let column = Column::from_str(name, false).map_err(|_| {
+                        let names: Vec<_> =
+                            Column::value_variants().iter().map(Column::name).collect();
+                        format!(
+                            "unknown placeholder {{{}}}, expected one of {}",
+                            name,
+                            names.join(", ")
+                        )
+                    })?;
+
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                    parts.push(Part::Field(column));
+                    chars = rest.chars();
+                }
+                '}' => {
+                    return Err(format!(
+                        "unmatched }} in {:?}, use }}}} for a brace",
+                        template
+                    ));
+                }
+                c => text.push(c),
             }
         }
 
-        deserializer.deserialize_str(DurationVisitor)
+        parts.push(Part::Text(text));
+
+        Ok(Template(parts))
     }
 }
 
-#[derive(Deserialize, Serialize)]
-enum ConfigWeatherProvider {
-    #[serde(rename = "open-meteo")]
-    OpenMeteo,
-    #[serde(rename = "open-weather-map")]
-    OpenWeatherMap,
+impl Template {
+    fn render(&self, config: &Config, weather: &WeatherData, current_time: &str) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(column) => column.value(config, weather, current_time),
+            })
+            .collect()
+    }
 }
 
-#[derive(Deserialize, Serialize)]
-#[serde(untagged)]
-#[derive(Clone)]
-enum ConfigLocation {
-    City(String, String),  // City, Country
-    Coordinates(f32, f32), // Latitude, Longitude
+/// The `--json` object: every `WeatherData` field plus `time`, `provider`,
+/// `provider_name` and `stale`.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    #[serde(flatten)]
+    weather: &'a WeatherData,
+    time: &'a str,
+    provider: &'a ConfigWeatherProvider,
+    /// The display name, e.g. `Open-Meteo`, next to the config name in `provider`.
+    provider_name: &'a str,
+    /// Whether this is an expired cached reading, shown because the network was down.
+    stale: bool,
 }
 
-#[derive(Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
-enum ConfigUnits {
-    Metric,
-    Imperial,
+/// The `--output waybar` object, also understood by Polybar scripts.
+#[derive(Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: &'static str,
 }
 
-#[derive(Deserialize, Serialize)]
-enum ConfigTimeFormat {
-    #[serde(rename = "24h")]
-    _24H,
-    #[serde(rename = "12h")]
-    _12H,
+#[derive(Subcommand)]
+enum Commands {
+    /// Write a config file with the default settings
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete the cached readings for every location
+    ClearCache,
 }
 
-#[derive(Deserialize, Serialize)]
-struct Config {
-    provider: ConfigWeatherProvider,
-    api_key: Option<String>,
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    /// Read the config from this file instead of ~/.config/weather-cli.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Annotate each value with how it changed since the previous reading
+    #[arg(long)]
+    diff: bool,
+    /// Store an API key in the OS keyring under the configured `api_key_keyring`
+    #[arg(long)]
+    set_key: bool,
+    /// Only resolve a "City, CC" pair to coordinates and region, then exit
+    #[arg(long, value_name = "CITY, COUNTRY")]
+    geocode: Option<String>,
+    /// Show the weather for "City,CC", "zip:CODE,CC" or "lat,lon" instead of the configured location
+    #[arg(long, value_name = "LOCATION", conflicts_with = "coords")]
     location: Option<ConfigLocation>,
-    units: ConfigUnits,
-    time_format: ConfigTimeFormat,
-    #[serde(with = "duration_format")]
-    caching_duration: Duration,
-}
-
-#[derive(Deserialize, Serialize)]
-struct WeatherData {
-    temperature: String,
-    feels_like: String,
-    wind_speed: String,
-    wind_direction: String,
-    condition: WeatherCondition,
-}
-
-#[derive(Deserialize, Serialize)]
-enum WeatherCondition {
-    Clear,
-    PartlyCloudy,
-    Overcast,
-    Foggy,
-    Drizzle,
-    Rainy,
-    Snowy,
-    SnowGrains,
-    RainShowers,
-    SnowShowers,
-    Thunderstorms,
-    Unknown,
-}
-
-#[derive(Deserialize)]
-struct MullvadResponse {
-    latitude: f32,
-    longitude: f32,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            api_key: None,
-            provider: ConfigWeatherProvider::OpenMeteo,
-            location: None,
-            units: ConfigUnits::Metric,
-            time_format: ConfigTimeFormat::_24H,
-            caching_duration: Duration::hours(1),
-        }
-    }
-}
-
-impl Display for ConfigWeatherProvider {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "https://{}",
-            match self {
-                ConfigWeatherProvider::OpenMeteo => "open-meteo.com".to_string(),
-                ConfigWeatherProvider::OpenWeatherMap => "openweathermap.org".to_string(),
+    /// Show the weather at a latitude and longitude, e.g. 52.52,13.40
+    #[arg(long, value_name = "LAT,LON", value_parser = parse_coordinates)]
+    coords: Option<ConfigLocation>,
+    /// Show the weather for a location from `[locations.<NAME>]` in the config
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["location", "coords"])]
+    profile: Option<String>,
+    /// Fetch from this provider instead of the configured ones, bypassing the cache
+    #[arg(long, value_name = "PROVIDER")]
+    provider: Option<ConfigWeatherProvider>,
+    /// Use metric or imperial units instead of `units`, bypassing the cache
+    #[arg(long, value_name = "UNITS")]
+    units: Option<ConfigUnits>,
+    /// Output format
+    #[arg(long, value_enum, default_value = "human")]
+    output: Output,
+    /// Columns printed by `--output table`, comma separated
+    /// [default: temp,feels_like,condition,wind,wind_direction,humidity,pressure,time]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    columns: Option<Vec<Column>>,
+    /// Print a header line with the column names in `--output table`
+    #[arg(long)]
+    header: bool,
+    /// Print one line built from placeholders, e.g. "{temp} {condition} {wind}"
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<Template>,
+    /// Print a forecast for this many days, one line per day
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u8).range(1..=16))]
+    forecast: Option<u8>,
+    /// Print a forecast for this many hours, one line per hour
+    #[arg(long, value_name = "HOURS", value_parser = clap::value_parser!(u8).range(1..=48))]
+    hourly: Option<u8>,
+    /// Print the forecast for the hour containing TIME, e.g. "in 3 hours" or "tomorrow morning"
+    #[arg(long, value_name = "TIME", value_parser = parse_at)]
+    at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Print the reading as a single JSON object
+    #[arg(long)]
+    json: bool,
+    /// Describe the weather in one sentence
+    #[arg(long)]
+    summary: bool,
+    /// Print only the temperature, e.g. 12°C
+    #[arg(long, short)]
+    quiet: bool,
+    /// Add a line with the European AQI and PM2.5, like `show_air_quality = true`
+    #[arg(long)]
+    air_quality: bool,
+    /// Add a line for each active government weather alert, like `show_alerts = true`
+    #[arg(long)]
+    alerts: bool,
+    /// Add a line with the moon phase, like `show_moon = true`
+    #[arg(long)]
+    moon: bool,
+    /// Add fields to the default output, e.g. dew_point,cloud_cover
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    show: Vec<ConfigField>,
+    /// Leave fields out of the default output, e.g. pressure,sun
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    hide: Vec<ConfigField>,
+    /// Prefix the condition with an emoji, like `show_icons = true`
+    #[arg(long)]
+    icons: bool,
+    /// Color the output: always, auto or never [default: the `color` config]
+    #[arg(long, value_name = "WHEN")]
+    color: Option<ConfigColor>,
+    /// Use plain ASCII instead of emoji and arrows, e.g. on the Linux console
+    #[arg(long)]
+    no_emoji_fallback: bool,
+    /// Print the URL each provider of the chain would request, without fetching
+    #[arg(long)]
+    dry_run: bool,
+    /// Report the requests made, cache hits and the location source on stderr
+    #[arg(long, short)]
+    verbose: bool,
+    /// Ring the terminal bell when a condition such as `temp<0` holds
+    #[arg(long, value_name = "CONDITION")]
+    bell_if: Option<alert::Condition>,
+    /// Ignore the cached reading and fetch a fresh one, which is then cached
+    #[arg(long)]
+    no_cache: bool,
+    /// Never look up the location from the IP address, like `allow_ip_geolocation = false`
+    #[arg(long)]
+    no_geolocation: bool,
+    /// Clear the screen and show the weather again every INTERVAL, e.g. `10min`
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        value_parser = parse_interval,
+        conflicts_with_all = ["forecast", "hourly", "at", "dry_run", "set_key", "geocode"]
+    )]
+    watch: Option<chrono::Duration>,
+    /// Fetch and cache the weather for every location under `[locations]`
+    #[arg(
+        long,
+        conflicts_with_all = ["forecast", "hourly", "at", "dry_run", "watch", "profile", "location", "coords"]
+    )]
+    prefetch: bool,
+    /// Fetch and cache a fresh reading without printing anything
+    #[arg(long, hide = true)]
+    refresh_cache: bool,
+    /// Print the reading as Prometheus gauges
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics: bool,
+}
+
+impl Args {
+    /// Rejects flag combinations that can't be honoured together. `--output
+    /// table`, `--output waybar`, `--summary`, `--quiet`, `--forecast`,
+    /// `--hourly`, `--at`, `--json` and `--metrics` each replace the default
+    /// layout and are mutually exclusive. `--diff`, `--color`, `--icons`,
+    /// `--air-quality`, `--moon`, `--alerts` and `--no-emoji-fallback` only
+    /// affect the default layout, `--columns` and `--header` only `--output
+    /// table`. `--format` replaces the default layout's lines with a single one.
+    fn validate(&self) -> Result<(), String> {
+        let table = matches!(self.output, Output::Table);
+        let mut modes = Vec::new();
+
+        if table {
+            modes.push("--output table");
+        }
+        if matches!(self.output, Output::Waybar) {
+            modes.push("--output waybar");
+        }
+        if self.summary {
+            modes.push("--summary");
+        }
+        if self.quiet {
+            modes.push("--quiet");
+        }
+        if self.forecast.is_some() {
+            modes.push("--forecast");
+        }
+        if self.hourly.is_some() {
+            modes.push("--hourly");
+        }
+        if self.at.is_some() {
+            modes.push("--at");
+        }
+        if self.json {
+            modes.push("--json");
+        }
+        #[cfg(feature = "metrics")]
+        if self.metrics {
+            modes.push("--metrics");
+        }
+
+        if modes.len() > 1 {
+            return Err(format!(
+                "{} are mutually exclusive, choose one output mode",
+                modes.join(", ")
+            ));
+        }
+
+        let human = modes.is_empty();
+        let modifiers = [
+            (self.diff, "--diff", human, "the default output"),
+            (
+                self.no_emoji_fallback,
+                "--no-emoji-fallback",
+                human,
+                "the default output",
+            ),
+            (self.color.is_some(), "--color", human, "the default output"),
+            (self.icons, "--icons", human, "the default output"),
+            (
+                self.air_quality,
+                "--air-quality",
+                human,
+                "the default output",
+            ),
+            (self.moon, "--moon", human, "the default output"),
+            (self.alerts, "--alerts", human, "the default output"),
+            (!self.show.is_empty(), "--show", human, "the default output"),
+            (!self.hide.is_empty(), "--hide", human, "the default output"),
+            (
+                self.format.is_some(),
+                "--format",
+                human,
+                "the default output",
+            ),
+            (self.columns.is_some(), "--columns", table, "--output table"),
+            (self.header, "--header", table, "--output table"),
+        ];
+
+        for (given, flag, applies, mode) in modifiers {
+            if given && !applies {
+                return Err(format!("{} can only be used with {}", flag, mode));
             }
-        )
+        }
+
+        Ok(())
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(err) = args.validate() {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, err)
+            .exit();
+    }
+    if let Some(Commands::Init { force }) = args.command {
+        if let Err(err) = init(args.config.as_deref(), force) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Commands::ClearCache) = args.command {
+        // A broken config still leaves the default cache directory to clear.
+        let config = read_config(args.config.as_deref()).unwrap_or_default();
+
+        match caching::clear(&config) {
+            Ok(1) => println!("Removed 1 cache file."),
+            Ok(removed) => println!("Removed {} cache files.", removed),
+            Err(err) => {
+                eprintln!("Failed to clear the cache. {}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut config = match read_config(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    config.verbose = args.verbose;
+    if let Err(err) = config.load_locations_file() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+
+    if args.set_key {
+        set_key(&config);
+        return;
+    }
+
+    if let Some(query) = &args.geocode {
+        geocode(&config, query, args.json);
+        return;
+    }
+
+    if let Err(err) = select_location(&mut config, &args, env::var(LOCATION_VAR).ok().as_deref()) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+    if let Some(provider) = args.provider {
+        config.provider = provider;
+        config.providers.clear();
+    }
+    if let Some(units) = args.units {
+        config.units = units;
+    }
+    if args.no_geolocation {
+        config.allow_ip_geolocation = false;
+    }
+    if let Err(errors) = config.validate() {
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        process::exit(1);
+    }
+    // The cache is keyed by the requested location, before gpsd or IP
+    // geolocation fill it in.
+    let cache_location = config.location.clone();
+    let cache_location = cache_location.as_ref();
+
+    if args.prefetch {
+        prefetch(&mut config);
+        return;
+    }
+
+    if args.refresh_cache {
+        if config.resolve_location().is_ok()
+            && let Ok(weather) = fetch_weather(&mut config)
+        {
+            caching::save(&config, cache_location, weather);
+        }
+        caching::unlock_refresh(&config, cache_location);
+        return;
+    }
+
+    if args.dry_run {
+        config
+            .resolve_location_uncached()
+            .unwrap_or_else(|err| location_failed(err));
+
+        for kind in config.provider_chain() {
+            let provider = provider(kind);
+
+            match provider.build_url(&config) {
+                Ok(url) => println!("{}", redact(&url)),
+                Err(err) => eprintln!("{}: {}", provider.name(), err),
+            }
+        }
+        return;
+    }
+
+    if let Some(days) = args.forecast {
+        // Like the current conditions, a cached forecast may be in other
+        // units or from another provider than the one asked for.
+        let no_cache = args.no_cache || args.provider.is_some() || args.units.is_some();
+
+        if !no_cache && let Some(forecast) = caching::load_forecast(&config, cache_location, days) {
+            print_forecast(&config, &forecast);
+            return;
+        }
+
+        config
+            .resolve_location()
+            .unwrap_or_else(|err| location_failed(err));
+
+        match with_fallback(&mut config, |provider, config| {
+            provider.fetch_forecast(config, days)
+        }) {
+            Ok(forecast) => {
+                print_forecast(&config, &forecast);
+                caching::save_forecast(&config, cache_location, forecast);
+            }
+            Err(err) => fetch_failed(&config, err),
+        }
+        return;
+    }
+
+    if let Some(hours) = args.hourly {
+        config
+            .resolve_location()
+            .unwrap_or_else(|err| location_failed(err));
+
+        match with_fallback(&mut config, |provider, config| {
+            provider.fetch_hourly(config, hours)
+        }) {
+            Ok(forecast) => print_hourly(&config, &forecast),
+            Err(err) => fetch_failed(&config, err),
+        }
+        return;
+    }
+
+    if let Some(time) = args.at {
+        config
+            .resolve_location()
+            .unwrap_or_else(|err| location_failed(err));
+
+        // The forecast starts with the current hour, so it takes one more
+        // entry than whole hours lie in between.
+        let ahead = time.signed_duration_since(chrono::Utc::now()).num_hours();
+        let hours = (ahead.max(0) + 2).min(AT_MAX_HOURS + 1) as u8;
+
+        match with_fallback(&mut config, |provider, config| {
+            provider.fetch_hourly(config, hours)
+        }) {
+            Ok(forecast) => match forecast.iter().rev().find(|hour| hour.time <= time) {
+                Some(hour) => print_hourly(&config, slice::from_ref(hour)),
+                None => {
+                    eprintln!(
+                        "The forecast doesn't cover {}.",
+                        config.format_time(config.localize(time, None))
+                    );
+                    process::exit(1);
+                }
+            },
+            Err(err) => fetch_failed(&config, err),
+        }
+        return;
+    }
+
+    let Some(interval) = args.watch else {
+        show(&args, &mut config, cache_location);
+        return;
+    };
+    let interval = interval.to_std().unwrap_or_default();
+    let terminal = io::stdout().is_terminal();
+    // Ctrl-C, SIGTERM and SIGHUP only raise the flag, so a refresh that's
+    // under way finishes printing before the loop ends.
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler = {
+        let stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || stop.store(true, Ordering::SeqCst))
+    };
+    if let Err(err) = handler {
+        eprintln!("Failed to install the signal handler. {}", err);
+        process::exit(1);
+    }
+
+    if terminal {
+        print!("\x1b[?25l");
+    }
+    while !stop.load(Ordering::SeqCst) {
+        if terminal {
+            print!("\x1b[2J\x1b[H");
+        }
+        show(&args, &mut config, cache_location);
+        io::stdout().flush().ok();
+
+        // An interval too long to reach simply waits for the signal.
+        let deadline = Instant::now().checked_add(interval);
+        while !stop.load(Ordering::SeqCst)
+            && deadline.is_none_or(|deadline| Instant::now() < deadline)
+        {
+            let left = deadline.map_or(WATCH_POLL, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            thread::sleep(WATCH_POLL.min(left));
+        }
+    }
+    if terminal {
+        // Resets the colors and shows the cursor again, leaving the last
+        // reading on screen.
+        print!("\x1b[0m\x1b[?25h");
+        io::stdout().flush().ok();
     }
 }
 
-impl Display for WeatherCondition {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
+/// How often `--watch` checks for a signal while waiting for the next refresh.
+const WATCH_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Pause between two fetches of `--prefetch`, so a long `[locations]` list
+/// doesn't run into the provider's rate limit.
+const PREFETCH_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Fetches and caches the weather for every named location that isn't cached
+/// and fresh yet, so reading them with `--profile` afterwards hits the cache.
+fn prefetch(config: &mut Config) {
+    if config.locations.is_empty() {
+        eprintln!("No [locations] are configured, nothing to prefetch.");
+        process::exit(1);
+    }
+
+    let locations: Vec<_> = config
+        .locations
+        .iter()
+        .map(|(name, profile)| (name.clone(), profile.location.clone()))
+        .collect();
+    let (mut fetched, mut fresh, mut failed) = (0, 0, 0);
+
+    for (name, location) in locations {
+        if caching::load(config, Some(&location)).is_some() {
+            fresh += 1;
+            continue;
+        }
+        if fetched + failed > 0 {
+            thread::sleep(PREFETCH_DELAY);
+        }
+
+        config.location = Some(location.clone());
+        config.location_origin = Some(format!("from profile '{}'", name));
+        match fetch_weather(config) {
+            Ok(weather) => {
+                caching::save(config, Some(&location), weather);
+                fetched += 1;
+            }
+            Err(err) => {
+                eprint!("{}: ", name);
+                report_fetch_failure(config, err);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("Fetched {}, already fresh {}.", fetched, fresh);
+    if failed > 0 {
+        eprintln!("Failed to fetch {}.", failed);
+        process::exit(1);
+    }
+}
+
+/// Overrides the configured location with `env`, the content of
+/// `WEATHER_CLI_LOCATION`, then the `--profile`, then `--location` or
+/// `--coords`. gpsd and IP geolocation only come in if none of these set one,
+/// in [`Config::resolve_location`].
+fn select_location(config: &mut Config, args: &Args, env: Option<&str>) -> Result<(), String> {
+    config.apply_env(env);
+    if let Some(profile) = &args.profile {
+        config.select_profile(profile)?;
+    }
+    if let Some(location) = &args.location {
+        config.location = Some(location.clone());
+        config.location_origin = Some("from --location".to_string());
+    }
+    if let Some(location) = &args.coords {
+        config.location = Some(location.clone());
+        config.location_origin = Some("from --coords".to_string());
+    }
+
+    Ok(())
+}
+
+/// Prints the current weather, from the cache while it's fresh. With
+/// `--watch`, a failed fetch is reported and skipped instead of ending the run.
+fn show(args: &Args, config: &mut Config, cache_location: Option<&ConfigLocation>) {
+    let watching = args.watch.is_some();
+    // Set only when the reading was just fetched from the provider, not served
+    // from the cache, so only fresh readings are written back.
+    let mut fetched_fresh = false;
+    // Set when the network was down and an expired cached reading stands in.
+    let mut stale = false;
+    // A cached reading may come from another provider, or be in other units,
+    // than the one asked for.
+    let no_cache = args.no_cache || args.provider.is_some() || args.units.is_some();
+
+    let weather = if !no_cache && let Some(data) = caching::load(config, cache_location) {
+        data
+    } else if !no_cache
+        && matches!(config.cache_mode, ConfigCacheMode::Swr)
+        && let Some(data) = caching::load_stale(config, cache_location)
+    {
+        config.log("Serving the stale reading, refreshing in the background");
+        refresh_in_background(config, args.config.as_deref(), cache_location);
+        data
+    } else {
+        let Some((mut weather, fresh)) = fetch_or_stale(config, cache_location, no_cache) else {
+            if watching {
+                return;
+            }
+            process::exit(1);
+        };
+        if fresh && !args.no_cache {
+            caching::complete(config, cache_location, &mut weather);
+        }
+        fetched_fresh = fresh;
+        stale = !fresh;
+        weather
+    };
+
+    // A fresh reading hasn't been saved yet, so the cached one is what it
+    // replaces. A cached reading is compared with the one it replaced.
+    let previous = match (args.diff, fetched_fresh) {
+        (false, _) => None,
+        (true, true) => caching::load_any(config, cache_location),
+        (true, false) => caching::load_previous(config, cache_location),
+    };
+    let previous = previous.as_ref();
+
+    let current_time = config.format_time(config.localize(chrono::Utc::now(), weather.utc_offset));
+
+    match args.output {
+        #[cfg(feature = "metrics")]
+        _ if args.metrics => print!("{}", weather_cli::metrics::exposition(config, &weather)),
+        _ if args.summary => println!("{}", summary(config, &weather)),
+        _ if args.quiet => println!("{}", weather.temperature),
+        _ if args.json => println!(
             "{}",
-            match self {
-                WeatherCondition::Clear => "Clear",
-                WeatherCondition::PartlyCloudy => "Partly Cloudy",
-                WeatherCondition::Overcast => "Overcast",
-                WeatherCondition::Foggy => "Foggy",
-                WeatherCondition::Drizzle => "Drizzle",
-                WeatherCondition::Rainy => "Rainy",
-                WeatherCondition::Snowy => "Snowy",
-                WeatherCondition::SnowGrains => "Snow Grains",
-                WeatherCondition::RainShowers => "Showers",
-                WeatherCondition::SnowShowers => "Showers",
-                WeatherCondition::Thunderstorms => "Thunderstorm",
-                WeatherCondition::Unknown => "Unknown",
+            serde_json::to_string(&JsonOutput {
+                weather: &weather,
+                time: &current_time,
+                provider: &config.provider,
+                provider_name: provider(config.provider).name(),
+                stale,
+            })
+            .unwrap()
+        ),
+        Output::Human => {
+            let icons = if args.no_emoji_fallback {
+                IconSet::Ascii
+            } else {
+                IconSet::detect()
+            };
+
+            let colors = args.color.unwrap_or(config.color).enabled();
+            config.show_icons |= args.icons;
+            let fields = fields(args, config);
+
+            match args.format.clone().or_else(|| config_format(config)) {
+                Some(template) => println!("{}", template.render(config, &weather, &current_time)),
+                None => {
+                    // Configured locations are already known by name.
+                    let place =
+                        if cache_location.is_none() && fields.contains(&ConfigField::Location) {
+                            place(config)
+                        } else {
+                            None
+                        };
+                    let air_quality = if fields.contains(&ConfigField::AirQuality) {
+                        air_quality(config)
+                    } else {
+                        None
+                    };
+                    let alerts = if fields.contains(&ConfigField::Alerts) {
+                        alerts(config)
+                    } else {
+                        Vec::new()
+                    };
+                    let moon = fields.contains(&ConfigField::Moon).then(|| {
+                        moon_phase(
+                            config
+                                .localize(chrono::Utc::now(), weather.utc_offset)
+                                .date_naive(),
+                        )
+                    });
+
+                    print_human(
+                        config,
+                        &weather,
+                        previous,
+                        place.as_deref(),
+                        air_quality.as_ref(),
+                        moon,
+                        &alerts,
+                        &current_time,
+                        &fields,
+                        stale,
+                        icons,
+                        colors,
+                    )
+                }
             }
-        )
+        }
+        Output::Table => print_table(config, &weather, &current_time, args),
+        Output::Waybar => println!(
+            "{}",
+            serde_json::to_string(&waybar(config, &weather)).unwrap()
+        ),
+    }
+
+    // The bell goes to stderr so it doesn't end up in piped output.
+    if let Some(condition) = &args.bell_if
+        && condition.matches(&weather)
+    {
+        eprint!("\x07");
+    }
+
+    if fetched_fresh {
+        caching::save(config, cache_location, weather);
     }
 }
 
-impl Config {
-    fn resolve_location(&mut self) {
-        if self.location.is_none() {
-            let res: MullvadResponse = blocking::get("https://ipv6.am.i.mullvad.net/json") // Seems to give the best results
-                .unwrap()
-                .json()
-                .unwrap();
+fn provider(kind: ConfigWeatherProvider) -> Box<dyn WeatherProvider> {
+    match kind {
+        ConfigWeatherProvider::OpenMeteo => Box::new(OpenMeteo),
+        ConfigWeatherProvider::OpenWeatherMap => Box::new(providers::OpenWeatherMap),
+        ConfigWeatherProvider::MetNo => Box::new(providers::MetNo),
+        ConfigWeatherProvider::WeatherApi => Box::new(providers::WeatherApiCom),
+        ConfigWeatherProvider::NationalWeatherService => {
+            Box::new(providers::NationalWeatherService)
+        }
+        ConfigWeatherProvider::Wttr => Box::new(providers::Wttr),
+    }
+}
+
+/// Tries each provider of the chain in turn, reporting failures on stderr,
+/// and leaves `config.provider` set to the one that answered. When all of
+/// them fail, the last error is returned.
+fn with_fallback<T>(
+    config: &mut Config,
+    fetch: impl Fn(&dyn WeatherProvider, &Config) -> Result<T, ProviderError>,
+) -> Result<T, ProviderError> {
+    let mut chain = config.provider_chain().into_iter().peekable();
+
+    loop {
+        let kind = chain.next().unwrap();
+        let current = provider(kind);
+        config.provider = kind;
+        config.log(&format!("Fetching from {}", current.name()));
 
-            self.location = Some(ConfigLocation::Coordinates(res.latitude, res.longitude));
+        match fetch(current.as_ref(), config) {
+            Err(err) if let Some(next) = chain.peek() => {
+                eprintln!(
+                    "{} failed, trying {}. {}",
+                    current.name(),
+                    provider(*next).name(),
+                    err
+                );
+            }
+            result => return result,
         }
     }
 }
 
-impl ConfigUnits {
-    fn temperature(&self) -> String {
-        match self {
-            ConfigUnits::Metric => "celsius",
-            ConfigUnits::Imperial => "fahrenheit",
+fn fetch_weather(config: &mut Config) -> Result<WeatherData, ProviderError> {
+    #[cfg(feature = "async")]
+    if config.provider_chain().len() > 1 {
+        return fetch_concurrently(config);
+    }
+
+    with_fallback(config, |provider, config| provider.fetch_weather(config))
+}
+
+/// Like [`with_fallback`], but every provider of the chain is asked at once.
+/// The readings are still taken in chain order, so a failing provider no
+/// longer delays the next one by its timeout and retries.
+#[cfg(feature = "async")]
+fn fetch_concurrently(config: &mut Config) -> Result<WeatherData, ProviderError> {
+    use futures_util::StreamExt;
+    use futures_util::stream::FuturesOrdered;
+    use providers::WeatherProviderAsync;
+
+    async fn fetch(
+        kind: ConfigWeatherProvider,
+        config: &Config,
+    ) -> Result<WeatherData, ProviderError> {
+        match kind {
+            ConfigWeatherProvider::OpenMeteo => {
+                WeatherProviderAsync::fetch_weather(&OpenMeteo, config).await
+            }
+            ConfigWeatherProvider::OpenWeatherMap => {
+                WeatherProviderAsync::fetch_weather(&providers::OpenWeatherMap, config).await
+            }
+            ConfigWeatherProvider::MetNo => {
+                WeatherProviderAsync::fetch_weather(&providers::MetNo, config).await
+            }
+            ConfigWeatherProvider::WeatherApi => {
+                WeatherProviderAsync::fetch_weather(&providers::WeatherApiCom, config).await
+            }
+            ConfigWeatherProvider::NationalWeatherService => {
+                WeatherProviderAsync::fetch_weather(&providers::NationalWeatherService, config)
+                    .await
+            }
+            ConfigWeatherProvider::Wttr => {
+                WeatherProviderAsync::fetch_weather(&providers::Wttr, config).await
+            }
         }
-        .to_string()
     }
 
-    fn speed(&self) -> String {
-        match self {
-            ConfigUnits::Metric => "kmh",
-            ConfigUnits::Imperial => "mph",
+    let chain = config.provider_chain();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to start the async runtime. {}", err);
+            process::exit(1);
+        });
+
+    let (answered, result) = runtime.block_on(async {
+        let config = &*config;
+        let mut pending: FuturesOrdered<_> = chain
+            .iter()
+            .map(|&kind| {
+                config.log(&format!("Fetching from {}", provider(kind).name()));
+                async move { (kind, fetch(kind, config).await) }
+            })
+            .collect();
+        let mut next = chain.iter().skip(1);
+
+        loop {
+            let (kind, result) = pending.next().await.unwrap();
+
+            match result {
+                Err(err) if let Some(&next) = next.next() => {
+                    eprintln!(
+                        "{} failed, trying {}. {}",
+                        provider(kind).name(),
+                        provider(next).name(),
+                        err
+                    );
+                }
+                result => return (kind, result),
+            }
         }
-        .to_string()
+    });
+
+    config.provider = answered;
+    result
+}
+
+/// Names the auto-resolved location. Like the air quality, a failure only
+/// costs the line.
+fn place(config: &mut Config) -> Option<String> {
+    config.resolve_location().ok()?;
+
+    match config.location {
+        Some(ConfigLocation::Coordinates(latitude, longitude)) => {
+            match config.place_name(latitude, longitude) {
+                Ok(place) => Some(place),
+                Err(err) => {
+                    eprintln!("Failed to look up the name of your location. {}", err);
+                    None
+                }
+            }
+        }
+        _ => None,
     }
+}
 
-    fn to_string(&self) -> String {
-        match self {
-            ConfigUnits::Metric => "metric",
-            ConfigUnits::Imperial => "imperial",
+/// Fetches the air quality, which isn't cached. A failure only costs the line,
+/// so it's reported on stderr instead of ending the run.
+fn air_quality(config: &mut Config) -> Option<AirQuality> {
+    if let Err(err) = config.resolve_location() {
+        eprintln!("{}", err);
+        return None;
+    }
+
+    let provider = provider(config.provider);
+
+    match provider.fetch_air_quality(config) {
+        Ok(air_quality) => Some(air_quality),
+        Err(ProviderError::Unsupported(feature)) => {
+            eprintln!("{} is not supported by {}.", feature, provider.name());
+            None
+        }
+        Err(err) => {
+            eprintln!("Failed to fetch the air quality. {}", err);
+            None
         }
-        .to_string()
     }
 }
 
-fn main() {
-    let mut config = read_config();
-    let provider: Box<dyn WeatherProvider> = match config.provider {
-        ConfigWeatherProvider::OpenMeteo => Box::new(OpenMeteo),
-        ConfigWeatherProvider::OpenWeatherMap => Box::new(providers::OpenWeatherMap),
+/// Fetches the active weather alerts, which aren't cached either. A failure
+/// is reported like the air quality's, rather than passed off as no alerts.
+fn alerts(config: &mut Config) -> Vec<WeatherAlert> {
+    if let Err(err) = config.resolve_location() {
+        eprintln!("{}", err);
+        return Vec::new();
+    }
+
+    let provider = provider(config.provider);
+
+    match provider.fetch_alerts(config) {
+        Ok(alerts) => alerts,
+        Err(ProviderError::Unsupported(feature)) => {
+            eprintln!("{} is not supported by {}.", feature, provider.name());
+            Vec::new()
+        }
+        Err(err) => {
+            eprintln!("Failed to fetch the weather alerts. {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// The `format` from the config, exiting if it doesn't parse.
+fn config_format(config: &Config) -> Option<Template> {
+    let format = config.format.as_deref()?;
+
+    match format.parse() {
+        Ok(template) => Some(template),
+        Err(err) => {
+            eprintln!("Invalid format in the config file. {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Fetches a fresh reading, returned with `true`. If the network is down, any
+/// cached reading stands in, however old, returned with `false`. Failures are
+/// reported; `None` means there was nothing to show.
+fn fetch_or_stale(
+    config: &mut Config,
+    cache_location: Option<&ConfigLocation>,
+    no_cache: bool,
+) -> Option<(WeatherData, bool)> {
+    let offline = match config.resolve_location() {
+        Ok(()) => match fetch_weather(config) {
+            Ok(weather) => return Some((weather, true)),
+            Err(err) => {
+                let offline = matches!(err, ProviderError::Http(_));
+
+                report_fetch_failure(config, err);
+                offline
+            }
+        },
+        Err(err) => {
+            let offline = matches!(err, LocationError::ServicesFailed(_));
+
+            eprintln!("{}", err);
+            offline
+        }
     };
 
-    let mut cache_hit = false;
+    if offline && !no_cache {
+        let weather = caching::load_any(config, cache_location)?;
 
-    let weather = if let Some(data) = caching::load(&config) {
-        data
+        config.log("Network is down, serving the cached reading");
+        return Some((weather, false));
+    }
+
+    None
+}
+
+fn location_failed(err: LocationError) -> ! {
+    eprintln!("{}", err);
+    process::exit(1);
+}
+
+fn fetch_failed(config: &Config, err: ProviderError) -> ! {
+    report_fetch_failure(config, err);
+    process::exit(1);
+}
+
+fn report_fetch_failure(config: &Config, err: ProviderError) {
+    let name = provider(config.provider).name();
+
+    match err {
+        ProviderError::Http(err) if err.is_timeout() => eprintln!(
+            "{} did not respond within {}s, see request_timeout in the config.",
+            name,
+            config.request_timeout.num_seconds()
+        ),
+        ProviderError::Unsupported(feature) => {
+            eprintln!("{} is not supported by {}.", feature, name)
+        }
+        err => eprintln!("Failed to fetch the weather from {}. {}", name, err),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn print_human(
+    config: &Config,
+    weather: &WeatherData,
+    previous: Option<&WeatherData>,
+    place: Option<&str>,
+    air_quality: Option<&AirQuality>,
+    moon: Option<MoonPhase>,
+    alerts: &[WeatherAlert],
+    current_time: &str,
+    fields: &[ConfigField],
+    stale: bool,
+    icons: IconSet,
+    colors: bool,
+) {
+    let temperature = match (
+        &config.temperature_icon_thresholds,
+        numeric_value(&weather.temperature),
+    ) {
+        (Some(thresholds), Some(value)) => {
+            format!("{} {}", thresholds.icon(value, icons), weather.temperature)
+        }
+        _ => weather.temperature.clone(),
+    };
+    let temperature = annotate(
+        temperature,
+        &weather.temperature,
+        previous.map(|p| p.temperature.as_str()),
+        "°",
+        icons,
+    );
+    let feels_like = annotate(
+        weather.feels_like.clone(),
+        &weather.feels_like,
+        previous.map(|p| p.feels_like.as_str()),
+        "°",
+        icons,
+    );
+    let wind_speed = annotate(
+        weather.wind_speed.clone(),
+        &weather.wind_speed,
+        previous.map(|p| p.wind_speed.as_str()),
+        "",
+        icons,
+    );
+    let wind_speed = match &weather.wind_gust {
+        Some(gust) => format!("{} (gusts {})", wind_speed, gust),
+        None => wind_speed,
+    };
+    let condition = if config.show_icons && icons == IconSet::Emoji {
+        format!(
+            "{} {}",
+            weather.condition.icon(weather.is_daytime()),
+            condition(config, weather)
+        )
+    } else {
+        condition(config, weather)
+    };
+    // The direction is already spelled out, so ASCII output just drops the arrow.
+    let wind_direction = match &config.wind_arrow {
+        Some(arrow) if icons == IconSet::Emoji => format!(
+            "{} {}",
+            arrow.arrow(weather.wind_degrees),
+            weather.wind_direction
+        ),
+        _ => weather.wind_direction.clone(),
+    };
+
+    let width = config.column_width.unwrap_or_else(|| {
+        [temperature.as_str(), condition.as_str(), current_time]
+            .into_iter()
+            .map(|label| display_width(label) + 1)
+            .fold(14, usize::max)
+    });
+
+    let temperature_color = if colors {
+        temperature_color(config, weather)
     } else {
-        config.resolve_location();
-        cache_hit = true;
-        provider.fetch_weather(&config).unwrap()
+        None
     };
+    let condition_color = if colors {
+        condition_color(&weather.condition)
+    } else {
+        None
+    };
+
+    let mut left = Vec::new();
+    let mut lines = Vec::new();
+    let mut time = false;
+
+    for field in fields {
+        match field {
+            ConfigField::Temperature => left.push(cell(&temperature, temperature_color, width)),
+            ConfigField::Condition => left.push(cell(&condition, condition_color, width)),
+            ConfigField::Time => time = true,
+            ConfigField::FeelsLike => lines.push(format!("feels like {}", feels_like)),
+            ConfigField::Wind => {
+                lines.push(format!("wind speed {} ({})", wind_speed, wind_direction))
+            }
+            ConfigField::Humidity => {
+                if let Some(humidity) = weather.humidity {
+                    let formatted = format!("{}%", humidity);
+                    let previous = previous.and_then(|p| p.humidity).map(|p| format!("{}%", p));
+
+                    lines.push(format!(
+                        "humidity {}",
+                        annotate(
+                            formatted.clone(),
+                            &formatted,
+                            previous.as_deref(),
+                            "%",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::DewPoint => {
+                if let Some(dew_point) = &weather.dew_point {
+                    lines.push(format!(
+                        "dew point {}",
+                        annotate(
+                            dew_point.clone(),
+                            dew_point,
+                            previous.and_then(|p| p.dew_point.as_deref()),
+                            "°",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::Pressure => {
+                if let Some(pressure) = weather.pressure {
+                    let formatted = config.pressure_unit.format(pressure);
+                    let previous = previous
+                        .and_then(|p| p.pressure)
+                        .map(|p| config.pressure_unit.format(p));
+
+                    lines.push(format!(
+                        "pressure {}",
+                        annotate(
+                            formatted.clone(),
+                            &formatted,
+                            previous.as_deref(),
+                            "",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::Visibility => {
+                // Like a missing reading, a visibility of 0 leaves the line out.
+                if let Some(visibility) = weather.visibility.filter(|&meters| meters > 0.0) {
+                    let formatted = config.format_visibility(visibility);
+                    let previous = previous
+                        .and_then(|p| p.visibility)
+                        .map(|p| config.format_visibility(p));
 
-    let current_time = match config.time_format {
-        ConfigTimeFormat::_24H => {
-            let now = chrono::Local::now();
-            now.format("%H:%M").to_string()
+                    lines.push(format!(
+                        "visibility {}",
+                        annotate(
+                            formatted.clone(),
+                            &formatted,
+                            previous.as_deref(),
+                            "",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::CloudCover => {
+                if let Some(cloud_cover) = weather.cloud_cover {
+                    let formatted = format!("{}%", cloud_cover);
+                    let previous = previous
+                        .and_then(|p| p.cloud_cover)
+                        .map(|p| format!("{}%", p));
+
+                    lines.push(format!(
+                        "cloud cover {}",
+                        annotate(
+                            formatted.clone(),
+                            &formatted,
+                            previous.as_deref(),
+                            "%",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::UvIndex => {
+                if let Some(uv_index) = weather.uv_index {
+                    let formatted = format!("{:.1}", uv_index);
+                    let previous = previous
+                        .and_then(|p| p.uv_index)
+                        .map(|p| format!("{:.1}", p));
+
+                    lines.push(format!(
+                        "uv index {}",
+                        annotate(
+                            formatted.clone(),
+                            &formatted,
+                            previous.as_deref(),
+                            "",
+                            icons,
+                        )
+                    ));
+                }
+            }
+            ConfigField::Precipitation => {
+                if let Some(precipitation) = precipitation(config, weather) {
+                    lines.push(format!("precipitation {}", precipitation));
+                }
+            }
+            ConfigField::Snow => {
+                if let Some(snow) = snow(config, weather) {
+                    lines.push(format!("snow {}", snow));
+                }
+            }
+            ConfigField::Sun => {
+                if let (Some(sunrise), Some(sunset)) = (weather.sunrise, weather.sunset) {
+                    lines.push(format!(
+                        "sunrise {}, sunset {}",
+                        config.format_time(config.localize(sunrise, weather.utc_offset)),
+                        config.format_time(config.localize(sunset, weather.utc_offset))
+                    ));
+                }
+            }
+            ConfigField::Moon => {
+                if let Some(moon) = moon {
+                    lines.push(match icons {
+                        IconSet::Emoji => format!("moon {} {}", moon.icon(), moon),
+                        IconSet::Ascii => format!("moon {}", moon),
+                    });
+                }
+            }
+            ConfigField::Alerts => {
+                let alert_label = match icons {
+                    IconSet::Emoji => "⚠️ alert",
+                    IconSet::Ascii => "alert",
+                };
+
+                for alert in alerts {
+                    lines.push(match &alert.headline {
+                        Some(headline) => {
+                            format!("{} {}: {}", alert_label, alert.event, headline)
+                        }
+                        None => format!("{} {}", alert_label, alert.event),
+                    });
+                }
+            }
+            ConfigField::Location => {
+                if let Some(place) = place {
+                    lines.push(format!("location {}", place));
+                }
+            }
+            ConfigField::AirQuality => {
+                if let Some(air_quality) = air_quality {
+                    let mut parts = Vec::new();
+
+                    if let Some(aqi) = air_quality.european_aqi {
+                        parts.push(format!("{} EAQI", aqi));
+                    }
+                    if let Some(pm2_5) = air_quality.pm2_5 {
+                        parts.push(format!("PM2.5 {:.1}µg/m³", pm2_5));
+                    }
+                    if !parts.is_empty() {
+                        lines.push(format!("air quality {}", parts.join(", ")));
+                    }
+                }
+            }
+            ConfigField::Provider if stale => {
+                lines.push(format!("{} (cached, possibly stale)", config.provider))
+            }
+            ConfigField::Provider => lines.push(config.provider.to_string()),
         }
-        ConfigTimeFormat::_12H => {
-            let now = chrono::Local::now();
-            now.format("%I:%M %p").to_string()
+    }
+
+    // The left column fills from the top, except for the time, which stays on
+    // the last line.
+    if time {
+        let rows = lines.len().max(left.len() + 1);
+
+        left.resize(rows - 1, cell("", None, width));
+        left.push(cell(current_time, None, width));
+    }
+    for row in 0..left.len().max(lines.len()) {
+        match (left.get(row), lines.get(row)) {
+            (Some(left), Some(line)) => println!("{}{}", left, line),
+            (Some(left), None) => println!("{}", left.trim_end()),
+            (None, Some(line)) => println!("{}{}", cell("", None, width), line),
+            (None, None) => {}
         }
+    }
+}
+
+/// The fields of the default output: `fields` or the default ones, with
+/// `--show` and the like added where they'd go by default and `--hide` removed.
+fn fields(args: &Args, config: &Config) -> Vec<ConfigField> {
+    let mut fields: Vec<ConfigField> = match &config.fields {
+        Some(fields) => fields.clone(),
+        None => DEFAULT_FIELDS
+            .iter()
+            .copied()
+            .filter(|field| match field {
+                ConfigField::DewPoint => config.show_dew_point,
+                ConfigField::CloudCover => config.show_cloud_cover,
+                ConfigField::Moon => config.show_moon,
+                ConfigField::Alerts => config.show_alerts,
+                ConfigField::AirQuality => config.show_air_quality,
+                _ => true,
+            })
+            .collect(),
     };
+    let shown = args
+        .show
+        .iter()
+        .copied()
+        .chain(args.air_quality.then_some(ConfigField::AirQuality))
+        .chain(args.alerts.then_some(ConfigField::Alerts))
+        .chain(args.moon.then_some(ConfigField::Moon));
+    let order = |field: &ConfigField| DEFAULT_FIELDS.iter().position(|f| f == field);
 
+    for field in shown {
+        if !fields.contains(&field) {
+            let position = fields
+                .iter()
+                .position(|f| order(f) > order(&field))
+                .unwrap_or(fields.len());
+
+            fields.insert(position, field);
+        }
+    }
+    fields.retain(|field| !args.hide.contains(field));
+
+    fields
+}
+
+fn waybar(config: &Config, weather: &WeatherData) -> WaybarOutput {
+    let mut tooltip = vec![
+        condition(config, weather),
+        format!("Feels like {}", weather.feels_like),
+        format!("Wind {} ({})", weather.wind_speed, weather.wind_direction),
+    ];
+    if let Some(humidity) = weather.humidity {
+        tooltip.push(format!("Humidity {}%", humidity));
+    }
+    if let Some(uv_index) = weather.uv_index {
+        tooltip.push(format!("UV index {:.1}", uv_index));
+    }
+    if let Some(precipitation) = precipitation(config, weather) {
+        tooltip.push(format!("Precipitation {}", precipitation));
+    }
+
+    WaybarOutput {
+        text: format!(
+            "{} {}",
+            weather.condition.icon(weather.is_daytime()),
+            weather.temperature
+        ),
+        tooltip: tooltip.join("\n"),
+        class: match weather.condition {
+            WeatherCondition::Clear => "clear",
+            WeatherCondition::PartlyCloudy => "partly_cloudy",
+            WeatherCondition::Overcast => "overcast",
+            WeatherCondition::Foggy => "foggy",
+            WeatherCondition::Drizzle => "drizzle",
+            WeatherCondition::Rainy => "rainy",
+            WeatherCondition::Snowy => "snowy",
+            WeatherCondition::SnowGrains => "snow_grains",
+            WeatherCondition::RainShowers => "rain_showers",
+            WeatherCondition::SnowShowers => "snow_showers",
+            WeatherCondition::Thunderstorms => "thunderstorms",
+            WeatherCondition::Unknown => "unknown",
+        },
+    }
+}
+
+fn print_forecast(config: &Config, forecast: &[DailyForecast]) {
+    let width = config.column_width.unwrap_or(14);
+
+    for day in forecast {
+        println!(
+            "{:<width$}{:<width$}{}",
+            day.date.format("%a %d %b").to_string(),
+            format!("{} / {}", day.high, day.low),
+            day.condition
+        );
+    }
+}
+
+fn print_hourly(config: &Config, forecast: &[HourlyForecast]) {
+    let width = config.column_width.unwrap_or(14);
+
+    for hour in forecast {
+        println!(
+            "{:<width$}{:<width$}{}",
+            config.format_time(config.localize(hour.time, hour.utc_offset)),
+            hour.temperature,
+            hour.condition
+        );
+    }
+}
+
+/// Pads `text` to the label column's `width`, wrapping only the text itself
+/// in the color so the escapes don't count towards the padding.
+fn cell(text: &str, color: Option<&str>, width: usize) -> String {
+    let padding = " ".repeat(width.saturating_sub(display_width(text)));
+
+    match color {
+        Some(color) => format!("\x1b[{}m{}\x1b[0m{}", color, text, padding),
+        None => format!("{}{}", text, padding),
+    }
+}
+
+/// Terminal columns taken up by `text`. Emoji are two columns wide, and so is
+/// a narrow symbol such as ☀ when followed by the emoji variation selector.
+fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut previous = 0;
+
+    for c in text.chars() {
+        let char_width = match c {
+            '\u{FE0F}' if previous == 1 => 1,
+            '\u{FE0F}' | '\u{200D}' => 0,
+            '\u{1F300}'..='\u{1FAFF}'
+            | '\u{2614}'
+            | '\u{26C4}'..='\u{26C5}'
+            | '\u{2753}'..='\u{2755}' => 2,
+            _ => 1,
+        };
+
+        width += char_width;
+        previous = char_width;
+    }
+
+    width
+}
+
+/// Blue at or below the cold threshold, red at or above the hot one. Without
+/// `temperature_icon_thresholds` the cutoffs are 5°C and 25°C.
+fn temperature_color(config: &Config, weather: &WeatherData) -> Option<&'static str> {
+    let temperature = numeric_value(&weather.temperature)?;
+    let unit = config.temperature_unit();
+    let thresholds = match &config.temperature_icon_thresholds {
+        Some(thresholds) => thresholds,
+        None => &ConfigTemperatureThresholds {
+            cold: unit.from_celsius(5.0),
+            hot: unit.from_celsius(25.0),
+        },
+    };
+
+    if temperature <= thresholds.cold {
+        Some("34")
+    } else if temperature >= thresholds.hot {
+        Some("31")
+    } else {
+        None
+    }
+}
+
+fn condition_color(condition: &WeatherCondition) -> Option<&'static str> {
+    match condition {
+        WeatherCondition::Clear => Some("33"),
+        WeatherCondition::PartlyCloudy => Some("37"),
+        WeatherCondition::Overcast | WeatherCondition::Foggy => Some("90"),
+        WeatherCondition::Drizzle | WeatherCondition::Rainy | WeatherCondition::RainShowers => {
+            Some("36")
+        }
+        WeatherCondition::Snowy | WeatherCondition::SnowGrains | WeatherCondition::SnowShowers => {
+            Some("97")
+        }
+        WeatherCondition::Thunderstorms => Some("35"),
+        WeatherCondition::Unknown => None,
+    }
+}
+
+/// Prints the selected columns in a fixed order, padded to a common width.
+/// Values never contain whitespace (spaces are replaced by `_`) and missing
+/// values are printed as `-`, so the output can be split on whitespace.
+fn print_table(config: &Config, weather: &WeatherData, current_time: &str, args: &Args) {
+    let values: Vec<(&str, String)> = args
+        .columns
+        .as_deref()
+        .unwrap_or(DEFAULT_COLUMNS)
+        .iter()
+        .map(|column| {
+            let value = column.value(config, weather, current_time);
+
+            (column.name(), value.replace(' ', "_"))
+        })
+        .collect();
+    let row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&values)
+            .map(|(cell, (name, value))| {
+                format!(
+                    "{:<1$}",
+                    cell,
+                    name.chars().count().max(value.chars().count())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    if args.header {
+        println!("{}", row(values.iter().map(|(name, _)| *name).collect()));
+    }
     println!(
-        "{:<14}feels like {}",
-        weather.temperature, weather.feels_like
-    );
-    println!(
-        "{:<14}wind speed {} ({})",
-        weather.condition.to_string(),
-        weather.wind_speed,
-        weather.wind_direction
+        "{}",
+        row(values.iter().map(|(_, value)| value.as_str()).collect())
     );
-    println!("{:<14}{}", current_time, config.provider);
+}
 
-    if cache_hit {
-        caching::save(weather);
+/// Composes a sentence like "Partly cloudy and 20°C, feels like 18°C, light
+/// breeze from the northeast."
+fn summary(config: &Config, weather: &WeatherData) -> String {
+    let condition = condition(config, weather).to_lowercase();
+    let mut chars = condition.chars();
+    let condition = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => condition,
+    };
+
+    let wind = match kilometers_per_hour(&weather.wind_speed) {
+        Some(speed) if speed < 2.0 => "calm".to_string(),
+        speed => {
+            let strength = match speed {
+                Some(speed) if speed < 20.0 => "light breeze",
+                Some(speed) if speed < 39.0 => "moderate wind",
+                Some(_) => "strong wind",
+                None => "wind",
+            };
+
+            format!(
+                "{} from the {}",
+                strength,
+                direction_name(&weather.wind_direction)
+            )
+        }
+    };
+
+    format!(
+        "{} and {}, feels like {}, {}.",
+        condition, weather.temperature, weather.feels_like, wind
+    )
+}
+
+fn direction_name(direction: &str) -> &str {
+    match direction {
+        "N" => "north",
+        "NNE" => "north-northeast",
+        "NE" => "northeast",
+        "ENE" => "east-northeast",
+        "E" => "east",
+        "ESE" => "east-southeast",
+        "SE" => "southeast",
+        "SSE" => "south-southeast",
+        "S" => "south",
+        "SSW" => "south-southwest",
+        "SW" => "southwest",
+        "WSW" => "west-southwest",
+        "W" => "west",
+        "WNW" => "west-northwest",
+        "NW" => "northwest",
+        "NNW" => "north-northwest",
+        direction => direction,
     }
 }
 
-fn read_config() -> Config {
-    let file = {
-        let mut path = home_dir().unwrap();
+/// The amount and chance of precipitation, e.g. `2.3mm, 80% chance`, with
+/// whichever part the provider reported.
+fn precipitation(config: &Config, weather: &WeatherData) -> Option<String> {
+    let amount = weather
+        .precipitation
+        .map(|mm| config.format_precipitation(mm));
+    let probability = weather
+        .precipitation_probability
+        .map(|probability| format!("{}% chance", probability));
 
-        path.push(".config");
-        path.push("weather-cli.toml");
+    match (amount, probability) {
+        (Some(amount), Some(probability)) => Some(format!("{}, {}", amount, probability)),
+        (amount, probability) => amount.or(probability),
+    }
+}
 
-        path
+/// New snow and the snow depth, e.g. `1.2cm new, 35.0cm deep`. Zeros are
+/// left out unless it's snowing.
+fn snow(config: &Config, weather: &WeatherData) -> Option<String> {
+    let snowing = weather.condition.is_snowy();
+    let snowfall = weather
+        .snowfall
+        .filter(|&cm| cm > 0.0 || snowing)
+        .map(|cm| format!("{} new", config.format_snow(cm)));
+    let depth = weather
+        .snow_depth
+        .filter(|&cm| cm > 0.0 || snowing)
+        .map(|cm| format!("{} deep", config.format_snow(cm)));
+
+    match (snowfall, depth) {
+        (Some(snowfall), Some(depth)) => Some(format!("{}, {}", snowfall, depth)),
+        (snowfall, depth) => snowfall.or(depth),
+    }
+}
+
+/// The condition label, falling back to the code description for codes
+/// without a dedicated variant.
+fn condition(config: &Config, weather: &WeatherData) -> String {
+    match (&weather.condition, weather.condition_code) {
+        (WeatherCondition::Unknown, Some(code)) => {
+            WeatherCondition::describe_code(&config.provider, code)
+                .map_or_else(|| weather.condition.to_string(), str::to_string)
+        }
+        _ => weather.condition.to_string(),
+    }
+}
+
+/// Starts a detached copy of this binary that refreshes the cache, unless one
+/// is already running.
+fn refresh_in_background(
+    config: &Config,
+    config_path: Option<&Path>,
+    location: Option<&ConfigLocation>,
+) {
+    if !caching::lock_refresh(config, location) {
+        return;
+    }
+
+    let spawned = env::current_exe().and_then(|exe| {
+        let mut command = Command::new(exe);
+
+        if let Some(path) = config_path {
+            command.arg("--config").arg(path);
+        }
+        // Passed explicitly so the child refreshes the same cache entry even
+        // when the location came from the command line.
+        if let Some(location) = location {
+            command.arg("--location").arg(location.to_string());
+        }
+        if !config.allow_ip_geolocation {
+            command.arg("--no-geolocation");
+        }
+
+        command
+            .arg("--refresh-cache")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+
+    if spawned.is_err() {
+        caching::unlock_refresh(config, location);
+    }
+}
+
+/// How far ahead `--at` may look, within the 48 hours `--hourly` offers.
+const AT_MAX_HOURS: i64 = 47;
+
+/// Resolves a `--at` phrase against the local time, see [`timespec::resolve`].
+fn parse_at(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let now = chrono::Local::now();
+    let time = timespec::resolve(value, now.naive_local())?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .ok_or_else(|| format!("'{}' falls into a skipped hour of the local time", value))?
+        .to_utc();
+    let ahead = time.signed_duration_since(now);
+
+    // A phrase like `now` resolves a moment before the forecast is fetched.
+    if ahead < -chrono::Duration::minutes(1) {
+        return Err(format!("'{}' lies in the past", value));
+    }
+    if ahead > chrono::Duration::hours(AT_MAX_HOURS) {
+        return Err(format!(
+            "'{}' lies more than {} hours ahead",
+            value, AT_MAX_HOURS
+        ));
+    }
+
+    Ok(time)
+}
+
+fn parse_interval(value: &str) -> Result<chrono::Duration, String> {
+    match parse_duration(value) {
+        Some(interval) if interval > chrono::Duration::zero() => Ok(interval),
+        Some(_) => Err("the interval must be longer than 0s".to_string()),
+        None => Err(format!(
+            "expected a duration such as 10min or 1h30min, got '{}'",
+            value
+        )),
+    }
+}
+
+fn parse_coordinates(value: &str) -> Result<ConfigLocation, String> {
+    let invalid = || {
+        format!(
+            "expected latitude,longitude such as 52.52,13.40, got '{}'",
+            value
+        )
+    };
+    let (latitude, longitude) = value.split_once(',').ok_or_else(invalid)?;
+    let latitude: f32 = latitude.trim().parse().map_err(|_| invalid())?;
+    let longitude: f32 = longitude.trim().parse().map_err(|_| invalid())?;
+
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(format!("latitude {} is outside -90..90", latitude));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(format!("longitude {} is outside -180..180", longitude));
+    }
+
+    Ok(ConfigLocation::Coordinates(latitude, longitude))
+}
+
+/// Prints what `query` geocodes to, as label and value lines or with `json`
+/// as the [`ResolvedLocation`] object.
+fn geocode(config: &Config, query: &str, json: bool) {
+    let Some((city, country)) = query.rsplit_once(',') else {
+        eprintln!("Expected a city and country code, e.g. \"Paris, FR\".");
+        process::exit(1);
     };
 
+    match OpenMeteo.geocode(config, city.trim(), country.trim()) {
+        Ok(location) if json => println!("{}", serde_json::to_string(&location).unwrap()),
+        Ok(location) => {
+            println!("{:<14}{}", "name", location.name);
+            println!(
+                "{:<14}{}, {}",
+                "coordinates", location.latitude, location.longitude
+            );
+            for (label, value) in [
+                ("region", &location.admin1),
+                ("country", &location.country_code),
+                ("timezone", &location.timezone),
+            ] {
+                if let Some(value) = value {
+                    println!("{:<14}{}", label, value);
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("Failed to geocode {}. {}", query, err);
+            process::exit(1);
+        }
+    }
+}
+
+fn set_key(config: &Config) {
+    let Some(reference) = &config.api_key_keyring else {
+        eprintln!("Set api_key_keyring in the config file first.");
+        process::exit(1);
+    };
+
+    print!("API key: ");
+
+    let mut key = String::new();
+    if let Err(err) = io::stdout()
+        .flush()
+        .and_then(|()| io::stdin().lock().read_line(&mut key))
+    {
+        eprintln!("Failed to read the API key. {}", err);
+        process::exit(1);
+    }
+    if key.trim().is_empty() {
+        eprintln!("No API key entered, the keyring is unchanged.");
+        process::exit(1);
+    }
+
+    match secrets::set(reference, key.trim()) {
+        Ok(()) => println!("Stored API key in keyring as {}.", reference),
+        Err(err) => {
+            eprintln!("Failed to store API key in keyring. {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+enum ConfigError {
+    HomeDirNotFound,
+    NotFound(PathBuf),
+    AlreadyExists(PathBuf),
+    Io(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::HomeDirNotFound => write!(f, "Could not determine the home directory."),
+            ConfigError::NotFound(path) => {
+                write!(f, "Config file {} does not exist.", path.display())
+            }
+            ConfigError::AlreadyExists(path) => write!(
+                f,
+                "Config file {} already exists, pass --force to overwrite it.",
+                path.display()
+            ),
+            ConfigError::Io(err) => write!(f, "Failed to read config file. {}", err),
+            // toml's messages span several lines with a snippet of the file.
+            ConfigError::Parse(err) => write!(f, "Failed to parse config file. {}", err.message()),
+        }
+    }
+}
+
+fn config_file(path: Option<&Path>) -> Result<PathBuf, ConfigError> {
+    match path {
+        Some(path) => Ok(path.to_path_buf()),
+        None => {
+            let mut path = home_dir().ok_or(ConfigError::HomeDirNotFound)?;
+
+            path.push(".config");
+            path.push("weather-cli.toml");
+
+            Ok(path)
+        }
+    }
+}
+
+/// Writes the default config to `path`, or the default location, and prints
+/// where it went.
+fn init(path: Option<&Path>, force: bool) -> Result<(), ConfigError> {
+    let file = config_file(path)?;
+
+    if file.exists() && !force {
+        return Err(ConfigError::AlreadyExists(file));
+    }
+
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).map_err(ConfigError::Io)?;
+    }
+    fs::write(&file, toml::to_string(&Config::default()).unwrap()).map_err(ConfigError::Io)?;
+
+    println!("Wrote default config to {}", file.display());
+
+    Ok(())
+}
+
+fn read_config(path: Option<&Path>) -> Result<Config, ConfigError> {
+    let file = config_file(path)?;
+
     if !file.exists() {
-        println!("Config file does not exist.");
+        // An explicitly chosen file is never silently replaced by defaults.
+        if path.is_some() {
+            return Err(ConfigError::NotFound(file));
+        }
+
+        eprintln!(
+            "Config file {} does not exist. Run `weather-cli init` to create it.",
+            file.display()
+        );
+        return Ok(Config::default());
     }
 
-    let content = fs::read_to_string(&file).unwrap();
+    let content = fs::read_to_string(&file).map_err(ConfigError::Io)?;
 
-    toml::from_str::<Config>(&content).unwrap_or_else(|err| {
-        println!("Failed to parse config file. {}", err);
-        Config::default()
-    })
+    toml::from_str::<Config>(&content).map_err(ConfigError::Parse)
 }
 
-fn parse_duration(string: &str) -> Option<Duration> {
-    if let Some(h_pos) = string.find("h") {
-        let hours: i64 = string[..h_pos].parse().ok()?;
-        Some(Duration::hours(hours))
-    } else if let Some(min_pos) = string.find("min") {
-        let minutes: i64 = string[..min_pos].parse().ok()?;
-        Some(Duration::minutes(minutes))
+/// Appends the change between two formatted values, e.g. `20°C (↑1°)`.
+/// Values that are unchanged, or that have no previous reading, are left as is.
+fn annotate(
+    display: String,
+    current: &str,
+    previous: Option<&str>,
+    suffix: &str,
+    icons: IconSet,
+) -> String {
+    let (Some(current), Some(previous)) =
+        (numeric_value(current), previous.and_then(numeric_value))
+    else {
+        return display;
+    };
+    let delta = current - previous;
+
+    if delta.abs() < 0.05 {
+        return display;
+    }
+
+    let arrow = match (icons, delta > 0.0) {
+        (IconSet::Emoji, true) => "↑",
+        (IconSet::Emoji, false) => "↓",
+        (IconSet::Ascii, true) => "+",
+        (IconSet::Ascii, false) => "-",
+    };
+    let amount = if delta.fract().abs() < 0.05 {
+        format!("{:.0}", delta.abs())
     } else {
-        None
+        format!("{:.1}", delta.abs())
+    };
+
+    format!("{} ({}{}{})", display, arrow, amount, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use weather_cli::ConfigProfile;
+
+    fn reading(condition: &str, wind_speed: &str, wind_direction: &str) -> WeatherData {
+        serde_json::from_value(serde_json::json!({
+            "temperature": "20°C",
+            "feels_like": "18°C",
+            "wind_speed": wind_speed,
+            "wind_direction": wind_direction,
+            "condition": condition,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn summary_sentences() {
+        let config = Config::default();
+        let cases = [
+            (
+                reading("PartlyCloudy", "12km/h", "NE"),
+                "Partly cloudy and 20°C, feels like 18°C, light breeze from the northeast.",
+            ),
+            (
+                reading("Clear", "1.5km/h", "S"),
+                "Clear and 20°C, feels like 18°C, calm.",
+            ),
+            (
+                reading("Rainy", "25mph", "WSW"),
+                "Rainy and 20°C, feels like 18°C, strong wind from the west-southwest.",
+            ),
+            (
+                reading("Overcast", "8m/s", "N"),
+                "Overcast and 20°C, feels like 18°C, moderate wind from the north.",
+            ),
+            (
+                reading("Foggy", "-", "X"),
+                "Foggy and 20°C, feels like 18°C, wind from the X.",
+            ),
+        ];
+
+        for (weather, sentence) in cases {
+            assert_eq!(summary(&config, &weather), sentence);
+        }
+    }
+
+    #[test]
+    fn summary_describes_unknown_conditions_by_code() {
+        let mut weather = reading("Unknown", "0km/h", "N");
+        weather.condition_code = Some(45);
+
+        assert_eq!(
+            summary(&Config::default(), &weather),
+            "Fog and 20°C, feels like 18°C, calm."
+        );
+    }
+
+    fn profiles() -> Config {
+        let mut config = Config::default();
+        config.location = Some(ConfigLocation::City("Berlin".into(), "DE".into()));
+        config.locations.insert(
+            "home".to_string(),
+            ConfigProfile {
+                location: ConfigLocation::City("Hamburg".into(), "DE".into()),
+            },
+        );
+
+        config
+    }
+
+    /// The location and where it came from, with `env` as
+    /// `WEATHER_CLI_LOCATION`.
+    fn selected(args: &[&str], env: Option<&str>) -> Result<(String, String), String> {
+        let args = Args::try_parse_from([&["weather-cli"], args].concat()).unwrap();
+        let mut config = profiles();
+
+        select_location(&mut config, &args, env)?;
+
+        Ok((
+            config.location.unwrap().to_string(),
+            config
+                .location_origin
+                .unwrap_or_else(|| "from the config".to_string()),
+        ))
+    }
+
+    #[test]
+    fn location_precedence() {
+        let paris = Some("Paris,FR");
+        let cases: [(&[&str], _, _, _); 9] = [
+            (&[], None, "Berlin,DE", "from the config"),
+            (&[], paris, "Paris,FR", "from WEATHER_CLI_LOCATION"),
+            // An unparsable variable is ignored.
+            (&[], Some("Paris"), "Berlin,DE", "from the config"),
+            (
+                &["--profile", "home"],
+                None,
+                "Hamburg,DE",
+                "from profile 'home'",
+            ),
+            (
+                &["--profile", "home"],
+                paris,
+                "Hamburg,DE",
+                "from profile 'home'",
+            ),
+            (
+                &["--location", "Rome,IT"],
+                None,
+                "Rome,IT",
+                "from --location",
+            ),
+            (
+                &["--location", "Rome,IT"],
+                paris,
+                "Rome,IT",
+                "from --location",
+            ),
+            (
+                &["--coords", "48.85,2.35"],
+                None,
+                "48.85,2.35",
+                "from --coords",
+            ),
+            (
+                &["--coords", "48.85,2.35"],
+                paris,
+                "48.85,2.35",
+                "from --coords",
+            ),
+        ];
+
+        for (args, env, location, origin) in cases {
+            assert_eq!(
+                selected(args, env),
+                Ok((location.to_string(), origin.to_string())),
+                "{:?} with {:?}",
+                args,
+                env
+            );
+        }
+
+        assert!(selected(&["--profile", "work"], None).is_err());
+        // A profile and an explicit location can't be combined.
+        assert!(
+            Args::try_parse_from(["weather-cli", "--profile", "home", "--location", "Paris,FR"])
+                .is_err()
+        );
     }
 }
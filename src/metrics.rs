@@ -0,0 +1,103 @@
+//! Prometheus text exposition of the current reading, meant for the
+//! node_exporter textfile collector or a scrape wrapper.
+//!
+//! Values are converted to base units regardless of the configured units.
+
+use crate::{
+    Config, ConfigLocation, ConfigTemperatureUnit, WeatherData, kilometers_per_hour, numeric_value,
+};
+use std::fmt::Write;
+
+fn unit(value: &str) -> &str {
+    value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+/// Falls back to the configured unit when the suffix style omits the letter.
+fn celsius(value: &str, config: &Config) -> Option<f32> {
+    let number = numeric_value(value)?;
+    let unit = unit(value).trim();
+    let fahrenheit = match unit {
+        "°" | "" => config.temperature_unit() == ConfigTemperatureUnit::Fahrenheit,
+        unit => unit.ends_with('F'),
+    };
+
+    if fahrenheit {
+        Some((number - 32.0) * 5.0 / 9.0)
+    } else if unit == "K" {
+        Some(number - 273.15)
+    } else {
+        Some(number)
+    }
+}
+
+fn location_label(config: &Config) -> String {
+    let location = config
+        .location
+        .as_ref()
+        .map_or("auto".to_string(), ConfigLocation::to_string);
+
+    location.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn exposition(config: &Config, weather: &WeatherData) -> String {
+    let location = location_label(config);
+    let gauges = [
+        (
+            "weather_temperature_celsius",
+            "Current air temperature.",
+            celsius(&weather.temperature, config),
+        ),
+        (
+            "weather_feels_like_celsius",
+            "Current apparent temperature.",
+            celsius(&weather.feels_like, config),
+        ),
+        (
+            "weather_wind_speed_meters_per_second",
+            "Current wind speed.",
+            kilometers_per_hour(&weather.wind_speed).map(|speed| speed / 3.6),
+        ),
+        (
+            "weather_wind_direction_degrees",
+            "Direction the wind is coming from.",
+            Some(weather.wind_degrees as f32),
+        ),
+        (
+            "weather_relative_humidity_percent",
+            "Current relative humidity.",
+            weather.humidity.map(f32::from),
+        ),
+        (
+            "weather_pressure_pascals",
+            "Current surface pressure.",
+            weather.pressure.map(|hpa| hpa * 100.0),
+        ),
+        (
+            "weather_precipitation_meters",
+            "Current precipitation.",
+            weather.precipitation.map(|mm| mm / 1000.0),
+        ),
+        (
+            "weather_precipitation_probability_ratio",
+            "Chance of precipitation.",
+            weather
+                .precipitation_probability
+                .map(|probability| f32::from(probability) / 100.0),
+        ),
+        ("weather_uv_index", "Current UV index.", weather.uv_index),
+    ];
+
+    let mut output = String::new();
+
+    for (name, help, value) in gauges {
+        let Some(value) = value else {
+            continue;
+        };
+
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} gauge", name);
+        let _ = writeln!(output, "{}{{location=\"{}\"}} {}", name, location, value);
+    }
+
+    output
+}
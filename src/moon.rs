@@ -0,0 +1,79 @@
+//! The moon phase, computed from the date alone so it works with any provider.
+
+use chrono::NaiveDate;
+use std::fmt::{Display, Formatter};
+
+/// The mean length of a lunation in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+const PHASES: [MoonPhase; 8] = [
+    MoonPhase::New,
+    MoonPhase::WaxingCrescent,
+    MoonPhase::FirstQuarter,
+    MoonPhase::WaxingGibbous,
+    MoonPhase::Full,
+    MoonPhase::WaningGibbous,
+    MoonPhase::LastQuarter,
+    MoonPhase::WaningCrescent,
+];
+
+/// The phase at noon UTC on `date`, counted in mean lunations from the new
+/// moon of 6 January 2000, 18:14 UTC. That's within about a day of the true
+/// phase, which is plenty for eight phases.
+pub fn moon_phase(date: NaiveDate) -> MoonPhase {
+    let reference = NaiveDate::from_ymd_opt(2000, 1, 6)
+        .and_then(|date| date.and_hms_opt(18, 14, 0))
+        .unwrap();
+    let noon = date.and_hms_opt(12, 0, 0).unwrap();
+    let days = (noon - reference).num_seconds() as f64 / (24.0 * 60.0 * 60.0);
+    let age = days.rem_euclid(SYNODIC_MONTH);
+
+    // Each phase spans an eighth of the cycle, centred on its exact point.
+    PHASES[(age / SYNODIC_MONTH * 8.0).round() as usize % 8]
+}
+
+impl MoonPhase {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "🌑",
+            MoonPhase::WaxingCrescent => "🌒",
+            MoonPhase::FirstQuarter => "🌓",
+            MoonPhase::WaxingGibbous => "🌔",
+            MoonPhase::Full => "🌕",
+            MoonPhase::WaningGibbous => "🌖",
+            MoonPhase::LastQuarter => "🌗",
+            MoonPhase::WaningCrescent => "🌘",
+        }
+    }
+}
+
+impl Display for MoonPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MoonPhase::New => "New Moon",
+                MoonPhase::WaxingCrescent => "Waxing Crescent",
+                MoonPhase::FirstQuarter => "First Quarter",
+                MoonPhase::WaxingGibbous => "Waxing Gibbous",
+                MoonPhase::Full => "Full Moon",
+                MoonPhase::WaningGibbous => "Waning Gibbous",
+                MoonPhase::LastQuarter => "Last Quarter",
+                MoonPhase::WaningCrescent => "Waning Crescent",
+            }
+        )
+    }
+}
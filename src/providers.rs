@@ -1,187 +1,1104 @@
-use crate::{Config, ConfigLocation, ConfigUnits, WeatherData};
-use reqwest::{blocking, Error as ReqwestError};
-use serde::{Deserialize, Serialize};
+use crate::{
+    AirQuality, Config, ConfigCompassPoints, ConfigFeelsLike, ConfigLocation,
+    ConfigTemperatureSuffixStyle,
+    ConfigTemperatureUnit::{self, Celsius, Kelvin},
+    ConfigWindUnit, DailyForecast, HourlyForecast, ResolvedLocation, WeatherAlert,
+    WeatherCondition, WeatherData, apparent,
+};
+use chrono::DateTime;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug)]
+pub enum ProviderError {
+    /// The request failed or timed out.
+    Http(reqwest::Error),
+    /// The response wasn't in the expected shape.
+    Deserialize(reqwest::Error),
+    /// The provider needs an API key and none is configured.
+    MissingApiKey,
+    /// The provider doesn't know the requested location.
+    LocationNotFound(String),
+    /// The provider rejected the API key as invalid or disabled.
+    InvalidApiKey,
+    /// The provider doesn't offer what was asked for, e.g. a forecast.
+    Unsupported(&'static str),
+    /// `Config::location` is unset; [`Config::resolve_location`] fills it in.
+    NoLocation,
+}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            ProviderError::Deserialize(err)
+        } else {
+            ProviderError::Http(err)
+        }
+    }
+}
+
+impl Display for ProviderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Http(err) => write!(f, "{}", err),
+            ProviderError::Deserialize(err) => write!(f, "Unexpected response. {}", err),
+            ProviderError::MissingApiKey => {
+                write!(
+                    f,
+                    "Missing API key, set WEATHER_CLI_API_KEY, api_key_file, api_key_keyring or api_key"
+                )
+            }
+            ProviderError::LocationNotFound(location) => {
+                write!(f, "No location found for {}, check your config", location)
+            }
+            ProviderError::InvalidApiKey => {
+                write!(
+                    f,
+                    "The API key was rejected, check that it's valid and enabled"
+                )
+            }
+            ProviderError::Unsupported(feature) => write!(f, "{} is not supported", feature),
+            ProviderError::NoLocation => write!(f, "No location set, resolve one first"),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
 
 pub trait WeatherProvider {
-    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ReqwestError>;
+    /// A human-readable name such as `Open-Meteo`, for messages and logs.
+    fn name(&self) -> &'static str;
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError>;
+
+    /// The URL `fetch_weather` requests, without sending it. A configured city
+    /// is still geocoded where the provider needs coordinates, and for the NWS
+    /// it's the first of two requests.
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError>;
+
+    /// Daily highs, lows and conditions for up to `days` days starting today.
+    fn fetch_forecast(
+        &self,
+        _config: &Config,
+        _days: u8,
+    ) -> Result<Vec<DailyForecast>, ProviderError> {
+        Err(ProviderError::Unsupported("A forecast"))
+    }
+
+    /// Temperatures and conditions for the next `hours` hours, starting with the current one.
+    fn fetch_hourly(
+        &self,
+        _config: &Config,
+        _hours: u8,
+    ) -> Result<Vec<HourlyForecast>, ProviderError> {
+        Err(ProviderError::Unsupported("An hourly forecast"))
+    }
+
+    /// The current air quality, from an endpoint separate from the weather.
+    fn fetch_air_quality(&self, _config: &Config) -> Result<AirQuality, ProviderError> {
+        Err(ProviderError::Unsupported("Air quality"))
+    }
+
+    /// Active government warnings for the location, empty if there are none.
+    fn fetch_alerts(&self, _config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        Err(ProviderError::Unsupported("Looking up weather alerts"))
+    }
+}
+
+/// Non-blocking counterpart of [`WeatherProvider`], built on `reqwest`'s async client.
+///
+/// The caller is responsible for driving the returned future on a Tokio runtime.
+#[cfg(feature = "async")]
+pub trait WeatherProviderAsync {
+    fn fetch_weather(
+        &self,
+        config: &Config,
+    ) -> impl Future<Output = Result<WeatherData, ProviderError>> + Send;
+
+    fn fetch_forecast(
+        &self,
+        _config: &Config,
+        _days: u8,
+    ) -> impl Future<Output = Result<Vec<DailyForecast>, ProviderError>> + Send {
+        async { Err(ProviderError::Unsupported("A forecast")) }
+    }
+
+    fn fetch_hourly(
+        &self,
+        _config: &Config,
+        _hours: u8,
+    ) -> impl Future<Output = Result<Vec<HourlyForecast>, ProviderError>> + Send {
+        async { Err(ProviderError::Unsupported("An hourly forecast")) }
+    }
+
+    fn fetch_air_quality(
+        &self,
+        _config: &Config,
+    ) -> impl Future<Output = Result<AirQuality, ProviderError>> + Send {
+        async { Err(ProviderError::Unsupported("Air quality")) }
+    }
+
+    fn fetch_alerts(
+        &self,
+        _config: &Config,
+    ) -> impl Future<Output = Result<Vec<WeatherAlert>, ProviderError>> + Send {
+        async { Err(ProviderError::Unsupported("Looking up weather alerts")) }
+    }
 }
 
 pub struct OpenMeteo;
 pub struct OpenWeatherMap;
+pub struct MetNo;
+pub struct WeatherApiCom;
+pub struct NationalWeatherService;
+pub struct Wttr;
 
-impl WeatherProvider for OpenMeteo {
-    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ReqwestError> {
-        let (latitude, longitude) = match &config.location.clone().unwrap() {
-            ConfigLocation::Coordinates(lat, lon) => (*lat, *lon),
-            ConfigLocation::City(city, country) => {
-                let url = format!(
-                    "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1&format=json&countryCode={}",
-                    city, country
-                );
-
-                #[derive(Serialize, Deserialize)]
-                struct Struct {
-                    pub latitude: f32,
-                    pub longitude: f32,
-                }
+mod open_meteo {
+    use serde::{Deserialize, Serialize};
 
-                #[derive(Serialize, Deserialize)]
-                struct Root {
-                    pub results: Vec<Struct>,
-                }
+    #[derive(Serialize, Deserialize)]
+    pub struct Struct {
+        pub name: String,
+        pub latitude: f32,
+        pub longitude: f32,
+        pub admin1: Option<String>,
+        pub country_code: Option<String>,
+        pub timezone: Option<String>,
+    }
 
-                let res: Root = blocking::get(url)?.json()?;
+    #[derive(Serialize, Deserialize)]
+    pub struct GeocodingRoot {
+        /// Left out entirely when nothing matched.
+        #[serde(default)]
+        pub results: Vec<Struct>,
+    }
 
-                let data = res
-                    .results
-                    .first()
-                    .expect("No City found, check your config");
+    #[derive(Serialize, Deserialize)]
+    pub struct Current {
+        pub time: i64,
+        pub interval: i32,
+        pub apparent_temperature: f32,
+        pub wind_speed_10m: f32,
+        pub wind_gusts_10m: Option<f32>,
+        pub wind_direction_10m: i16,
+        pub temperature_2m: f32,
+        pub weather_code: i32,
+        pub surface_pressure: f32,
+        pub relative_humidity_2m: f32,
+        pub dew_point_2m: Option<f32>,
+        /// In meters.
+        pub visibility: Option<f32>,
+        pub cloud_cover: Option<f32>,
+        pub uv_index: Option<f32>,
+        pub precipitation: Option<f32>,
+        /// In cm.
+        pub snowfall: Option<f32>,
+        /// In meters.
+        pub snow_depth: Option<f32>,
+        /// 1 during the day, 0 at night.
+        pub is_day: Option<u8>,
+    }
 
-                (data.latitude, data.longitude)
-            }
-        };
+    #[derive(Serialize, Deserialize)]
+    pub struct CurrentUnits {
+        pub time: String,
+        pub interval: String,
+        pub apparent_temperature: String,
+        pub wind_speed_10m: String,
+        pub wind_direction_10m: String,
+        pub temperature_2m: String,
+        pub weather_code: String,
+        pub surface_pressure: String,
+        pub relative_humidity_2m: String,
+        pub uv_index: String,
+        pub precipitation: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Sun {
+        pub sunrise: Vec<i64>,
+        pub sunset: Vec<i64>,
+        #[serde(default)]
+        pub precipitation_probability_max: Vec<Option<f32>>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Root {
+        pub current_units: CurrentUnits,
+        pub current: Current,
+        pub daily: Option<Sun>,
+        #[serde(default)]
+        pub utc_offset_seconds: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Daily {
+        pub time: Vec<chrono::NaiveDate>,
+        pub temperature_2m_max: Vec<f32>,
+        pub temperature_2m_min: Vec<f32>,
+        pub weather_code: Vec<i32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct DailyRoot {
+        pub daily: Daily,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Hourly {
+        pub time: Vec<i64>,
+        pub temperature_2m: Vec<f32>,
+        pub weather_code: Vec<i32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct HourlyRoot {
+        pub hourly: Hourly,
+        #[serde(default)]
+        pub utc_offset_seconds: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct AirQualityRoot {
+        pub current: crate::AirQuality,
+    }
+}
+
+mod open_weather_map {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Alert {
+        pub event: String,
+        pub description: String,
+    }
+
+    /// One Call 3.0 leaves `alerts` out when there are none.
+    #[derive(Serialize, Deserialize)]
+    pub struct OneCall {
+        #[serde(default)]
+        pub alerts: Vec<Alert>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Wind {
+        pub deg: i16,
+        pub speed: f32,
+        pub gust: Option<f32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Struct {
+        pub description: String,
+        pub icon: String,
+        pub id: i64,
+        pub main: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Main {
+        pub feels_like: f64,
+        pub temp: f64,
+        pub pressure: Option<f32>,
+        pub humidity: Option<u8>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Clouds {
+        /// Cloud cover in percent.
+        pub all: u8,
+    }
+
+    /// Rain or snow, in mm regardless of `units`.
+    #[derive(Serialize, Deserialize)]
+    pub struct Volume {
+        #[serde(rename = "1h")]
+        pub one_hour: Option<f32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Sys {
+        pub sunrise: Option<i64>,
+        pub sunset: Option<i64>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Root {
+        pub main: Main,
+        pub weather: Vec<Struct>,
+        pub wind: Wind,
+        /// In meters, at most 10 km.
+        pub visibility: Option<f32>,
+        pub clouds: Option<Clouds>,
+        pub sys: Option<Sys>,
+        pub rain: Option<Volume>,
+        pub snow: Option<Volume>,
+    }
+}
+
+mod met_no {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Details {
+        pub air_temperature: f32,
+        pub air_pressure_at_sea_level: Option<f32>,
+        pub relative_humidity: Option<f32>,
+        pub cloud_area_fraction: Option<f32>,
+        pub wind_from_direction: f32,
+        pub wind_speed: f32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Instant {
+        pub details: Details,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Summary {
+        pub symbol_code: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct PeriodDetails {
+        pub precipitation_amount: Option<f32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Period {
+        pub summary: Summary,
+        pub details: Option<PeriodDetails>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Data {
+        pub instant: Instant,
+        pub next_1_hours: Option<Period>,
+        pub next_6_hours: Option<Period>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Timestep {
+        pub data: Data,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Properties {
+        pub timeseries: Vec<Timestep>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Root {
+        pub properties: Properties,
+    }
+}
+
+mod weather_api {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Location {
+        pub name: String,
+        pub region: Option<String>,
+        pub lat: f32,
+        pub lon: f32,
+        pub tz_id: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Condition {
+        pub code: i32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Current {
+        pub temp_c: f32,
+        pub temp_f: f32,
+        pub feelslike_c: f32,
+        pub feelslike_f: f32,
+        pub wind_kph: f32,
+        pub gust_kph: Option<f32>,
+        pub wind_mph: f32,
+        pub wind_degree: i16,
+        pub pressure_mb: Option<f32>,
+        pub humidity: Option<u8>,
+        pub vis_km: Option<f32>,
+        pub cloud: Option<u8>,
+        pub uv: Option<f32>,
+        pub precip_mm: Option<f32>,
+        /// 1 during the day, 0 at night.
+        pub is_day: Option<u8>,
+        pub condition: Condition,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Root {
+        pub location: Location,
+        pub current: Current,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Alert {
+        pub event: String,
+        pub headline: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Alerts {
+        pub alert: Vec<Alert>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct AlertsRoot {
+        pub alerts: Alerts,
+    }
+
+    /// The body WeatherAPI.com answers failed requests with.
+    #[derive(Serialize, Deserialize)]
+    pub struct ErrorRoot {
+        pub error: Error,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Error {
+        pub code: u32,
+    }
+}
+
+mod nws {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct RelativeLocationProperties {
+        pub city: String,
+        pub state: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct RelativeLocation {
+        pub properties: RelativeLocationProperties,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PointProperties {
+        pub forecast_hourly: String,
+        pub relative_location: Option<RelativeLocation>,
+        pub time_zone: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Point {
+        pub properties: PointProperties,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Quantity {
+        pub value: Option<f32>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Period {
+        pub temperature: f32,
+        pub relative_humidity: Option<Quantity>,
+        pub probability_of_precipitation: Option<Quantity>,
+        pub wind_speed: String,
+        pub wind_direction: String,
+        pub short_forecast: String,
+        pub is_daytime: Option<bool>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ForecastProperties {
+        pub periods: Vec<Period>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Forecast {
+        pub properties: ForecastProperties,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct AlertProperties {
+        pub event: String,
+        pub headline: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct AlertFeature {
+        pub properties: AlertProperties,
+    }
+
+    /// A GeoJSON collection of the alerts in effect at a point.
+    #[derive(Serialize, Deserialize)]
+    pub struct Alerts {
+        pub features: Vec<AlertFeature>,
+    }
+}
+
+mod wttr {
+    use serde::{Deserialize, Serialize};
+
+    /// wttr.in sends every number as a string.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Current {
+        #[serde(rename = "temp_C")]
+        pub temp_c: String,
+        #[serde(rename = "temp_F")]
+        pub temp_f: String,
+        #[serde(rename = "FeelsLikeC")]
+        pub feels_like_c: String,
+        #[serde(rename = "FeelsLikeF")]
+        pub feels_like_f: String,
+        pub windspeed_kmph: String,
+        pub windspeed_miles: String,
+        pub winddir_degree: String,
+        pub humidity: Option<String>,
+        pub pressure: Option<String>,
+        /// In km.
+        pub visibility: Option<String>,
+        pub cloudcover: Option<String>,
+        pub uv_index: Option<String>,
+        #[serde(rename = "precipMM")]
+        pub precip_mm: Option<String>,
+        pub weather_code: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Value {
+        pub value: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Area {
+        pub area_name: Vec<Value>,
+        #[serde(default)]
+        pub region: Vec<Value>,
+        pub latitude: String,
+        pub longitude: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Root {
+        pub current_condition: Vec<Current>,
+        #[serde(default)]
+        pub nearest_area: Vec<Area>,
+    }
+}
+
+impl OpenMeteo {
+    fn geocoding_url(city: &str, country: &str) -> String {
+        format!(
+            "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1&format=json&countryCode={}",
+            city, country
+        )
+    }
+
+    fn resolved_location(
+        res: open_meteo::GeocodingRoot,
+        city: &str,
+        country: &str,
+    ) -> Result<ResolvedLocation, ProviderError> {
+        let data = res
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| ProviderError::LocationNotFound(format!("{}, {}", city, country)))?;
+
+        Ok(ResolvedLocation {
+            latitude: data.latitude,
+            longitude: data.longitude,
+            name: data.name,
+            admin1: data.admin1,
+            country_code: data.country_code,
+            timezone: data.timezone,
+        })
+    }
 
-        let url = format!(
-            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&models=best_match&current=apparent_temperature,wind_speed_10m,wind_direction_10m,temperature_2m,weather_code&temperature_unit={}&wind_speed_unit={}",
+    /// Resolves a city and country code to coordinates and region metadata.
+    pub fn geocode(
+        &self,
+        config: &Config,
+        city: &str,
+        country: &str,
+    ) -> Result<ResolvedLocation, ProviderError> {
+        Self::resolved_location(
+            config.get(&Self::geocoding_url(city, country))?.json()?,
+            city,
+            country,
+        )
+    }
+
+    fn forecast_url(latitude: f32, longitude: f32, config: &Config) -> String {
+        format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&models=best_match&current=apparent_temperature,wind_speed_10m,wind_gusts_10m,wind_direction_10m,temperature_2m,weather_code,surface_pressure,relative_humidity_2m,dew_point_2m,visibility,cloud_cover,uv_index,precipitation,snowfall,snow_depth,is_day&daily=sunrise,sunset,precipitation_probability_max&forecast_days=1&timezone=auto&timeformat=unixtime&temperature_unit={}&wind_speed_unit={}",
             latitude,
             longitude,
-            &config.units.temperature(),
-            &config.units.speed(),
-        );
+            config.temperature_unit().open_meteo(),
+            config.wind_unit().open_meteo(),
+        )
+    }
 
-        #[derive(Serialize, Deserialize)]
-        struct Current {
-            pub time: String,
-            pub interval: i32,
-            pub apparent_temperature: f32,
-            pub wind_speed_10m: f32,
-            pub wind_direction_10m: i16,
-            pub temperature_2m: f32,
-            pub weather_code: i32,
+    /// Maps a WMO weather interpretation code to a condition.
+    fn condition(code: i32) -> WeatherCondition {
+        use crate::WeatherCondition::*;
+        match code {
+            0 | 1 => Clear,
+            2 => PartlyCloudy,
+            3 => Overcast,
+            45 | 48 => Foggy,
+            51 | 53 | 55 | 56 | 57 => Drizzle,
+            61 | 63 | 65 | 66 | 67 => Rainy,
+            71 | 73 | 75 => Snowy,
+            77 => SnowGrains,
+            80..=82 => RainShowers,
+            85 | 86 => SnowShowers,
+            95 | 96 | 99 => Thunderstorms,
+            _ => Unknown,
         }
+    }
 
-        #[derive(Serialize, Deserialize)]
-        struct CurrentUnits {
-            pub time: String,
-            pub interval: String,
-            pub apparent_temperature: String,
-            pub wind_speed_10m: String,
-            pub wind_direction_10m: String,
-            pub temperature_2m: String,
-            pub weather_code: String,
-        }
+    fn air_quality_url(latitude: f32, longitude: f32) -> String {
+        format!(
+            "https://air-quality-api.open-meteo.com/v1/air-quality?latitude={}&longitude={}&current=european_aqi,pm2_5",
+            latitude, longitude
+        )
+    }
+
+    fn daily_url(latitude: f32, longitude: f32, days: u8, config: &Config) -> String {
+        format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&models=best_match&daily=temperature_2m_max,temperature_2m_min,weather_code&timezone=auto&forecast_days={}&temperature_unit={}",
+            latitude,
+            longitude,
+            days,
+            config.temperature_unit().open_meteo(),
+        )
+    }
+
+    fn daily_forecast(res: open_meteo::DailyRoot, config: &Config) -> Vec<DailyForecast> {
+        let daily = res.daily;
+        let unit = config.temperature_unit().open_meteo();
+
+        daily
+            .time
+            .into_iter()
+            .zip(daily.temperature_2m_max)
+            .zip(daily.temperature_2m_min)
+            .zip(daily.weather_code)
+            .map(|(((date, high), low), code)| DailyForecast {
+                date,
+                high: format_temperature(high, unit, config),
+                low: format_temperature(low, unit, config),
+                condition: Self::condition(code),
+            })
+            .collect()
+    }
+
+    fn hourly_url(latitude: f32, longitude: f32, hours: u8, config: &Config) -> String {
+        format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&models=best_match&hourly=temperature_2m,weather_code&timezone=auto&timeformat=unixtime&forecast_hours={}&temperature_unit={}",
+            latitude,
+            longitude,
+            hours,
+            config.temperature_unit().open_meteo(),
+        )
+    }
+
+    fn hourly_forecast(res: open_meteo::HourlyRoot, config: &Config) -> Vec<HourlyForecast> {
+        let hourly = res.hourly;
+        let unit = config.temperature_unit().open_meteo();
+
+        hourly
+            .time
+            .into_iter()
+            .zip(hourly.temperature_2m)
+            .zip(hourly.weather_code)
+            .filter_map(|((time, temperature), code)| {
+                Some(HourlyForecast {
+                    time: DateTime::from_timestamp(time, 0)?,
+                    utc_offset: res.utc_offset_seconds,
+                    temperature: format_temperature(temperature, unit, config),
+                    condition: Self::condition(code),
+                })
+            })
+            .collect()
+    }
+
+    /// The coordinates to query, geocoding a configured city or postal code
+    /// first. Open-Meteo's geocoder searches postal codes like place names.
+    fn coordinates(
+        &self,
+        config: &Config,
+    ) -> Result<((f32, f32), Option<ResolvedLocation>), ProviderError> {
+        match location(config)? {
+            ConfigLocation::Coordinates(lat, lon) => Ok(((*lat, *lon), None)),
+            ConfigLocation::City(city, country) | ConfigLocation::PostalCode(city, country) => {
+                let location = self.geocode(config, city, country)?;
 
-        #[derive(Serialize, Deserialize)]
-        struct Root {
-            pub current_units: CurrentUnits,
-            pub current: Current,
+                Ok(((location.latitude, location.longitude), Some(location)))
+            }
         }
+    }
 
-        let res: Root = blocking::get(url)?.json()?;
+    fn weather_data(
+        res: open_meteo::Root,
+        location: Option<ResolvedLocation>,
+        config: &Config,
+    ) -> WeatherData {
+        // Requested in `wind_unit` already, except for Beaufort, which comes as km/h.
+        let wind_speed = |value: f32| match config.wind_unit() {
+            ConfigWindUnit::Beaufort => format_wind_speed(value, config),
+            unit => unit.format(value),
+        };
+        let kmh = |value: f32| match config.wind_unit() {
+            ConfigWindUnit::Beaufort => value,
+            unit => value / unit.from_kmh(1.0),
+        };
 
-        Ok(WeatherData {
-            temperature: format!(
-                "{}{}",
-                res.current.temperature_2m as i32, res.current_units.temperature_2m
+        WeatherData {
+            temperature: format_temperature(
+                res.current.temperature_2m,
+                config.temperature_unit().open_meteo(),
+                config,
             ),
-            feels_like: format!(
-                "{}{}",
-                res.current.apparent_temperature as i32, res.current_units.apparent_temperature
+            feels_like: format_feels_like(
+                res.current.apparent_temperature,
+                config.temperature_unit().open_meteo(),
+                (
+                    config
+                        .temperature_unit()
+                        .open_meteo()
+                        .to_celsius(res.current.temperature_2m),
+                    Some(res.current.relative_humidity_2m),
+                    kmh(res.current.wind_speed_10m),
+                ),
+                config,
             ),
-            wind_speed: format!(
-                "{}{}",
-                res.current.wind_speed_10m, res.current_units.wind_speed_10m
+            wind_speed: wind_speed(res.current.wind_speed_10m),
+            wind_gust: res.current.wind_gusts_10m.map(wind_speed),
+            wind_direction: degree_to_direction(
+                res.current.wind_direction_10m,
+                config.wind_direction_points,
             ),
-            wind_direction: degree_to_direction(res.current.wind_direction_10m),
-            condition: {
-                use crate::WeatherCondition::*;
-                match res.current.weather_code {
-                    0 | 1 => Clear,
-                    2 => PartlyCloudy,
-                    3 => Overcast,
-                    45 | 48 => Foggy,
-                    51 | 53 | 55 | 56 | 57 => Drizzle,
-                    61 | 63 | 65 | 66 | 67 => Rainy,
-                    71 | 73 | 75 => Snowy,
-                    77 => SnowGrains,
-                    80..=82 => RainShowers,
-                    85 | 86 => SnowShowers,
-                    95 | 96 | 99 => Thunderstorms,
-                    _ => Unknown,
-                }
-            },
-        })
+            wind_degrees: res.current.wind_direction_10m,
+            humidity: Some(res.current.relative_humidity_2m.round() as u8),
+            dew_point: res.current.dew_point_2m.map(|dew_point| {
+                format_temperature(dew_point, config.temperature_unit().open_meteo(), config)
+            }),
+            pressure: Some(res.current.surface_pressure),
+            visibility: res.current.visibility,
+            cloud_cover: res.current.cloud_cover.map(|cover| cover.round() as u8),
+            uv_index: res.current.uv_index,
+            precipitation: res.current.precipitation,
+            precipitation_probability: res
+                .daily
+                .as_ref()
+                .and_then(|daily| daily.precipitation_probability_max.first().copied())
+                .flatten()
+                .map(|probability| probability.round() as u8),
+            snowfall: res.current.snowfall,
+            snow_depth: res.current.snow_depth.map(|meters| meters * 100.0),
+            sunrise: res
+                .daily
+                .as_ref()
+                .and_then(|daily| daily.sunrise.first())
+                .and_then(|&time| DateTime::from_timestamp(time, 0)),
+            sunset: res
+                .daily
+                .as_ref()
+                .and_then(|daily| daily.sunset.first())
+                .and_then(|&time| DateTime::from_timestamp(time, 0)),
+            utc_offset: res.utc_offset_seconds,
+            is_day: res.current.is_day.map(|is_day| is_day == 1),
+            condition: Self::condition(res.current.weather_code),
+            condition_code: Some(res.current.weather_code),
+            location,
+        }
     }
 }
 
-impl WeatherProvider for OpenWeatherMap {
-    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ReqwestError> {
-        let api_key = if let Some(api_key) = &config.api_key {
-            api_key
-        } else {
-            panic!("Missing API key");
-        };
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &'static str {
+        "Open-Meteo"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = self.coordinates(config)?;
+
+        let res = config
+            .get(&Self::forecast_url(latitude, longitude, config))?
+            .json()?;
+
+        Ok(Self::weather_data(res, location, config))
+    }
+
+    fn fetch_forecast(
+        &self,
+        config: &Config,
+        days: u8,
+    ) -> Result<Vec<DailyForecast>, ProviderError> {
+        let ((latitude, longitude), _) = self.coordinates(config)?;
+
+        let res = config
+            .get(&Self::daily_url(latitude, longitude, days, config))?
+            .json()?;
+
+        Ok(Self::daily_forecast(res, config))
+    }
+
+    fn fetch_hourly(
+        &self,
+        config: &Config,
+        hours: u8,
+    ) -> Result<Vec<HourlyForecast>, ProviderError> {
+        let ((latitude, longitude), _) = self.coordinates(config)?;
+
+        let res = config
+            .get(&Self::hourly_url(latitude, longitude, hours, config))?
+            .json()?;
+
+        Ok(Self::hourly_forecast(res, config))
+    }
+
+    fn fetch_air_quality(&self, config: &Config) -> Result<AirQuality, ProviderError> {
+        let ((latitude, longitude), _) = self.coordinates(config)?;
+
+        let res: open_meteo::AirQualityRoot = config
+            .get(&Self::air_quality_url(latitude, longitude))?
+            .json()?;
+
+        Ok(res.current)
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        let ((latitude, longitude), _) = self.coordinates(config)?;
+
+        Ok(Self::forecast_url(latitude, longitude, config))
+    }
+}
+
+#[cfg(feature = "async")]
+impl OpenMeteo {
+    async fn coordinates_async(
+        config: &Config,
+    ) -> Result<((f32, f32), Option<ResolvedLocation>), ProviderError> {
+        match location(config)? {
+            ConfigLocation::Coordinates(lat, lon) => Ok(((*lat, *lon), None)),
+            ConfigLocation::City(city, country) | ConfigLocation::PostalCode(city, country) => {
+                let location = Self::resolved_location(
+                    config
+                        .get_async(&Self::geocoding_url(city, country))
+                        .await?
+                        .json()
+                        .await?,
+                    city,
+                    country,
+                )?;
+
+                Ok(((location.latitude, location.longitude), Some(location)))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for OpenMeteo {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = Self::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::forecast_url(latitude, longitude, config))
+            .await?
+            .json()
+            .await?;
+
+        Ok(Self::weather_data(res, location, config))
+    }
+
+    async fn fetch_forecast(
+        &self,
+        config: &Config,
+        days: u8,
+    ) -> Result<Vec<DailyForecast>, ProviderError> {
+        let ((latitude, longitude), _) = Self::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::daily_url(latitude, longitude, days, config))
+            .await?
+            .json()
+            .await?;
+
+        Ok(Self::daily_forecast(res, config))
+    }
+
+    async fn fetch_hourly(
+        &self,
+        config: &Config,
+        hours: u8,
+    ) -> Result<Vec<HourlyForecast>, ProviderError> {
+        let ((latitude, longitude), _) = Self::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::hourly_url(latitude, longitude, hours, config))
+            .await?
+            .json()
+            .await?;
+
+        Ok(Self::hourly_forecast(res, config))
+    }
+
+    async fn fetch_air_quality(&self, config: &Config) -> Result<AirQuality, ProviderError> {
+        let ((latitude, longitude), _) = Self::coordinates_async(config).await?;
+
+        let res: open_meteo::AirQualityRoot = config
+            .get_async(&Self::air_quality_url(latitude, longitude))
+            .await?
+            .json()
+            .await?;
+
+        Ok(res.current)
+    }
+}
 
-        let location = match &config.location.clone().unwrap() {
+impl OpenWeatherMap {
+    fn weather_url(config: &Config) -> Result<String, ProviderError> {
+        let api_key = config.api_key().ok_or(ProviderError::MissingApiKey)?;
+
+        let location = match location(config)? {
             ConfigLocation::Coordinates(lat, lon) => {
                 format!("lat={}&lon={}", lat, lon)
             }
             ConfigLocation::City(city, country) => {
                 format!("q={},{}", city, country)
             }
+            ConfigLocation::PostalCode(code, country) => {
+                format!("zip={},{}", code, country)
+            }
         };
 
-        let url = format!(
+        Ok(format!(
             "https://api.openweathermap.org/data/2.5/weather?{}&appid={}&units={}",
             location,
             api_key,
-            &config.units.to_string()
-        );
-
-        #[derive(Serialize, Deserialize)]
-        struct Wind {
-            pub deg: i16,
-            pub speed: f32,
-        }
+            config.temperature_unit().open_weather_map()
+        ))
+    }
 
-        #[derive(Serialize, Deserialize)]
-        struct Struct {
-            pub description: String,
-            pub icon: String,
-            pub id: i64,
-            pub main: String,
-        }
+    /// One Call 3.0, the only OpenWeatherMap API with alerts. It needs a
+    /// subscription of its own, even for the free calls.
+    fn alerts_url(latitude: f32, longitude: f32, config: &Config) -> Result<String, ProviderError> {
+        let api_key = config.api_key().ok_or(ProviderError::MissingApiKey)?;
 
-        #[derive(Serialize, Deserialize)]
-        struct Main {
-            pub feels_like: f64,
-            pub temp: f64,
-        }
+        Ok(format!(
+            "https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&exclude=current,minutely,hourly,daily&appid={}",
+            latitude, longitude, api_key
+        ))
+    }
 
-        #[derive(Serialize, Deserialize)]
-        struct Root {
-            pub main: Main,
-            pub weather: Vec<Struct>,
-            pub wind: Wind,
-        }
+    /// OpenWeatherMap has no headline, so the first line of the description
+    /// stands in for it.
+    fn alerts(res: open_weather_map::OneCall) -> Vec<WeatherAlert> {
+        res.alerts
+            .into_iter()
+            .map(|alert| WeatherAlert {
+                event: alert.event,
+                headline: alert
+                    .description
+                    .lines()
+                    .next()
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string),
+            })
+            .collect()
+    }
 
-        let res: Root = blocking::get(url)?.json()?;
+    /// A city OpenWeatherMap doesn't know is answered with a 404 and a body of
+    /// `{"cod":"404","message":"city not found"}` instead of a reading.
+    fn not_found(config: &Config) -> ProviderError {
+        ProviderError::LocationNotFound(match &config.location {
+            Some(ConfigLocation::City(city, country))
+            | Some(ConfigLocation::PostalCode(city, country)) => format!("{}, {}", city, country),
+            Some(ConfigLocation::Coordinates(latitude, longitude)) => {
+                format!("{}, {}", latitude, longitude)
+            }
+            None => "your location".to_string(),
+        })
+    }
 
-        let temp_unit = match &config.units {
-            ConfigUnits::Imperial => "°F",
-            ConfigUnits::Metric => "°C",
+    fn weather_data(res: open_weather_map::Root, config: &Config) -> WeatherData {
+        // The units parameter decides the wind speed unit too: mph for
+        // imperial, m/s for metric and standard.
+        let kmh = |speed: f32| match config.temperature_unit() {
+            ConfigTemperatureUnit::Fahrenheit => speed * 1.609_344,
+            ConfigTemperatureUnit::Celsius | ConfigTemperatureUnit::Kelvin => speed * 3.6,
         };
 
-        let wind_speed = match &config.units {
-            ConfigUnits::Metric => format!("{:.1}km/h", res.wind.speed), // Documentation says that it returns the ms, but it seems like it returns km/h
-            ConfigUnits::Imperial => format!("{}mph", res.wind.speed),
-        };
+        WeatherData {
+            temperature: format_temperature(
+                res.main.temp as f32,
+                config.temperature_unit(),
+                config,
+            ),
+            feels_like: format_feels_like(
+                res.main.feels_like as f32,
+                config.temperature_unit(),
+                (
+                    config.temperature_unit().to_celsius(res.main.temp as f32),
+                    res.main.humidity.map(f32::from),
+                    kmh(res.wind.speed),
+                ),
+                config,
+            ),
+            wind_speed: format_wind_speed(kmh(res.wind.speed), config),
+            wind_gust: res
+                .wind
+                .gust
+                .map(|gust| format_wind_speed(kmh(gust), config)),
+            wind_direction: degree_to_direction(res.wind.deg, config.wind_direction_points),
+            wind_degrees: res.wind.deg,
+            humidity: res.main.humidity,
+            dew_point: res.main.humidity.and_then(|humidity| {
+                let celsius = config.temperature_unit().to_celsius(res.main.temp as f32);
 
-        Ok(WeatherData {
-            temperature: format!("{}{}", res.main.temp as i32, temp_unit),
-            feels_like: format!("{}{}", res.main.feels_like as i32, temp_unit),
-            wind_speed,
-            wind_direction: degree_to_direction(res.wind.deg),
+                format_dew_point(celsius, humidity.into(), config)
+            }),
+            pressure: res.main.pressure,
+            visibility: res.visibility,
+            cloud_cover: res.clouds.map(|clouds| clouds.all),
+            // Not part of the free current weather endpoint.
+            uv_index: None,
+            precipitation: match (
+                res.rain.as_ref().and_then(|rain| rain.one_hour),
+                res.snow.as_ref().and_then(|snow| snow.one_hour),
+            ) {
+                (None, None) => None,
+                (rain, snow) => Some(rain.unwrap_or_default() + snow.unwrap_or_default()),
+            },
+            precipitation_probability: None,
+            // Reported in mm.
+            snowfall: res
+                .snow
+                .as_ref()
+                .and_then(|snow| snow.one_hour)
+                .map(|mm| mm / 10.0),
+            snow_depth: None,
+            sunrise: res
+                .sys
+                .as_ref()
+                .and_then(|sys| sys.sunrise)
+                .and_then(|time| DateTime::from_timestamp(time, 0)),
+            sunset: res
+                .sys
+                .as_ref()
+                .and_then(|sys| sys.sunset)
+                .and_then(|time| DateTime::from_timestamp(time, 0)),
+            utc_offset: None,
+            is_day: res
+                .weather
+                .first()
+                .map(|weather| !weather.icon.ends_with('n')),
             condition: {
                 use crate::WeatherCondition::*;
                 match res.weather.first() {
@@ -201,21 +1118,1141 @@ impl WeatherProvider for OpenWeatherMap {
                     None => Unknown,
                 }
             },
+            condition_code: res.weather.first().map(|weather| weather.id as i32),
+            location: None,
+        }
+    }
+}
+
+impl WeatherProvider for OpenWeatherMap {
+    fn name(&self) -> &'static str {
+        "OpenWeatherMap"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config.get(&Self::weather_url(config)?)?;
+
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Self::not_found(config));
+        }
+
+        let res = res.error_for_status()?.json()?;
+
+        Ok(Self::weather_data(res, config))
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        Self::weather_url(config)
+    }
+
+    fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo.coordinates(config)?;
+
+        let res = config
+            .get(&Self::alerts_url(latitude, longitude, config)?)?
+            .error_for_status()?
+            .json()?;
+
+        Ok(Self::alerts(res))
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for OpenWeatherMap {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config.get_async(&Self::weather_url(config)?).await?;
+
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Self::not_found(config));
+        }
+
+        let res = res.error_for_status()?.json().await?;
+
+        Ok(Self::weather_data(res, config))
+    }
+
+    async fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::alerts_url(latitude, longitude, config)?)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Self::alerts(res))
+    }
+}
+
+impl MetNo {
+    /// Met.no asks for no more than four decimals so responses can be cached.
+    fn forecast_url(latitude: f32, longitude: f32) -> String {
+        format!(
+            "https://api.met.no/weatherapi/locationforecast/2.0/compact?lat={:.4}&lon={:.4}",
+            latitude, longitude
+        )
+    }
+
+    /// Symbol codes end in `_day`, `_night` or `_polartwilight` for the
+    /// symbols that differ by daylight.
+    fn is_day(symbol_code: &str) -> Option<bool> {
+        match symbol_code.rsplit_once('_')?.1 {
+            "day" => Some(true),
+            "night" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Maps a symbol code such as `partlycloudy_day` or `lightrainshowers` to a
+    /// condition, ignoring the day, night and polar twilight variants.
+    fn condition(symbol_code: &str) -> WeatherCondition {
+        use crate::WeatherCondition::*;
+        let symbol = symbol_code.split('_').next().unwrap_or_default();
+
+        if symbol.contains("thunder") {
+            return Thunderstorms;
+        }
+
+        match symbol {
+            "clearsky" | "fair" => Clear,
+            "partlycloudy" => PartlyCloudy,
+            "cloudy" => Overcast,
+            "fog" => Foggy,
+            "lightrain" | "rain" | "heavyrain" => Rainy,
+            "lightrainshowers" | "rainshowers" | "heavyrainshowers" => RainShowers,
+            "lightsleet" | "sleet" | "heavysleet" | "lightsnow" | "snow" | "heavysnow" => Snowy,
+            "lightsleetshowers" | "sleetshowers" | "heavysleetshowers" | "lightsnowshowers"
+            | "snowshowers" | "heavysnowshowers" => SnowShowers,
+            _ => Unknown,
+        }
+    }
+
+    /// The first timestep is the current hour.
+    fn current(
+        res: met_no::Root,
+        latitude: f32,
+        longitude: f32,
+    ) -> Result<met_no::Timestep, ProviderError> {
+        res.properties
+            .timeseries
+            .into_iter()
+            .next()
+            .ok_or_else(|| ProviderError::LocationNotFound(format!("{}, {}", latitude, longitude)))
+    }
+
+    /// Met.no always answers in °C and m/s.
+    fn weather_data(
+        now: met_no::Timestep,
+        location: Option<ResolvedLocation>,
+        config: &Config,
+    ) -> WeatherData {
+        let details = now.data.instant.details;
+        let feels_like = apparent_temperature(
+            details.air_temperature,
+            details.relative_humidity.unwrap_or_default(),
+            details.wind_speed,
+        );
+        let wind_speed = format_wind_speed(details.wind_speed * 3.6, config);
+        let wind_degrees = details.wind_from_direction.round() as i16;
+        // Only the next hour's amount is comparable to other providers' readings.
+        let precipitation = now
+            .data
+            .next_1_hours
+            .as_ref()
+            .and_then(|period| period.details.as_ref())
+            .and_then(|details| details.precipitation_amount);
+        let symbol_code = now
+            .data
+            .next_1_hours
+            .or(now.data.next_6_hours)
+            .map(|period| period.summary.symbol_code);
+
+        WeatherData {
+            temperature: format_temperature(details.air_temperature, Celsius, config),
+            feels_like: format_feels_like(
+                feels_like,
+                Celsius,
+                (
+                    details.air_temperature,
+                    details.relative_humidity,
+                    details.wind_speed * 3.6,
+                ),
+                config,
+            ),
+            wind_speed,
+            wind_gust: None,
+            wind_direction: degree_to_direction(wind_degrees, config.wind_direction_points),
+            wind_degrees,
+            humidity: details
+                .relative_humidity
+                .map(|humidity| humidity.round() as u8),
+            dew_point: details
+                .relative_humidity
+                .and_then(|humidity| format_dew_point(details.air_temperature, humidity, config)),
+            pressure: details.air_pressure_at_sea_level,
+            visibility: None,
+            cloud_cover: details
+                .cloud_area_fraction
+                .map(|fraction| fraction.round() as u8),
+            uv_index: None,
+            precipitation,
+            precipitation_probability: None,
+            snowfall: None,
+            snow_depth: None,
+            sunrise: None,
+            sunset: None,
+            utc_offset: None,
+            is_day: symbol_code.as_deref().and_then(Self::is_day),
+            condition: symbol_code
+                .as_deref()
+                .map_or(WeatherCondition::Unknown, Self::condition),
+            condition_code: None,
+            location,
+        }
+    }
+}
+
+impl WeatherProvider for MetNo {
+    fn name(&self) -> &'static str {
+        "MET Norway"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = OpenMeteo.coordinates(config)?;
+
+        let res = config
+            .get(&Self::forecast_url(latitude, longitude))?
+            .error_for_status()?
+            .json()?;
+        let now = Self::current(res, latitude, longitude)?;
+
+        Ok(Self::weather_data(now, location, config))
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo.coordinates(config)?;
+
+        Ok(Self::forecast_url(latitude, longitude))
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for MetNo {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = OpenMeteo::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::forecast_url(latitude, longitude))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let now = Self::current(res, latitude, longitude)?;
+
+        Ok(Self::weather_data(now, location, config))
+    }
+}
+
+impl WeatherApiCom {
+    fn weather_url(config: &Config) -> Result<String, ProviderError> {
+        let api_key = config.api_key().ok_or(ProviderError::MissingApiKey)?;
+
+        let location = match location(config)? {
+            ConfigLocation::Coordinates(lat, lon) => format!("{},{}", lat, lon),
+            ConfigLocation::City(city, country) => format!("{},{}", city, country),
+            // WeatherAPI.com looks up US ZIP codes and UK and Canadian postcodes
+            // without a country.
+            ConfigLocation::PostalCode(code, _) => code.clone(),
+        };
+
+        Ok(format!(
+            "https://api.weatherapi.com/v1/current.json?key={}&q={}",
+            api_key, location
+        ))
+    }
+
+    /// The same query as [`WeatherApiCom::weather_url`], against the alerts endpoint.
+    fn alerts_url(config: &Config) -> Result<String, ProviderError> {
+        Ok(Self::weather_url(config)?.replacen("/current.json", "/alerts.json", 1))
+    }
+
+    /// Maps the error codes in a failed response's body: 1006 is an unknown
+    /// location, 2006 an invalid and 2008 a disabled key. Anything else is
+    /// reported as the HTTP error.
+    fn error(config: &Config, err: reqwest::Error, body: &str) -> ProviderError {
+        match serde_json::from_str::<weather_api::ErrorRoot>(body).map(|res| res.error.code) {
+            Ok(1006) => ProviderError::LocationNotFound(match &config.location {
+                Some(location) => location.to_string(),
+                None => "your location".to_string(),
+            }),
+            Ok(2006 | 2008) => ProviderError::InvalidApiKey,
+            _ => ProviderError::Http(err),
+        }
+    }
+
+    fn alerts(res: weather_api::AlertsRoot) -> Vec<WeatherAlert> {
+        res.alerts
+            .alert
+            .into_iter()
+            .map(|alert| WeatherAlert {
+                event: alert.event,
+                headline: alert.headline,
+            })
+            .collect()
+    }
+
+    /// Maps a WeatherAPI.com condition code to a condition.
+    fn condition(code: i32) -> WeatherCondition {
+        use crate::WeatherCondition::*;
+        match code {
+            1000 => Clear,
+            1003 => PartlyCloudy,
+            1006 | 1009 => Overcast,
+            1030 | 1135 | 1147 => Foggy,
+            1072 | 1150 | 1153 | 1168 | 1171 => Drizzle,
+            1063 | 1180..=1201 => Rainy,
+            1066 | 1069 | 1114 | 1117 | 1204..=1225 => Snowy,
+            1237 | 1261 | 1264 => SnowGrains,
+            1240..=1246 => RainShowers,
+            1249..=1258 => SnowShowers,
+            1087 | 1273..=1282 => Thunderstorms,
+            _ => Unknown,
+        }
+    }
+
+    fn weather_data(res: weather_api::Root, config: &Config) -> WeatherData {
+        let current = res.current;
+        let wind_speed = format_wind_speed(current.wind_kph, config);
+
+        WeatherData {
+            temperature: format_temperature(current.temp_c, Celsius, config),
+            feels_like: format_feels_like(
+                current.feelslike_c,
+                Celsius,
+                (
+                    current.temp_c,
+                    current.humidity.map(f32::from),
+                    current.wind_kph,
+                ),
+                config,
+            ),
+            wind_speed,
+            wind_gust: current.gust_kph.map(|gust| format_wind_speed(gust, config)),
+            wind_direction: degree_to_direction(current.wind_degree, config.wind_direction_points),
+            wind_degrees: current.wind_degree,
+            humidity: current.humidity,
+            dew_point: current
+                .humidity
+                .and_then(|humidity| format_dew_point(current.temp_c, humidity.into(), config)),
+            pressure: current.pressure_mb,
+            visibility: current.vis_km.map(|km| km * 1000.0),
+            cloud_cover: current.cloud,
+            uv_index: current.uv,
+            precipitation: current.precip_mm,
+            precipitation_probability: None,
+            snowfall: None,
+            snow_depth: None,
+            sunrise: None,
+            sunset: None,
+            utc_offset: None,
+            is_day: current.is_day.map(|is_day| is_day == 1),
+            condition: Self::condition(current.condition.code),
+            condition_code: Some(current.condition.code),
+            location: Some(ResolvedLocation {
+                latitude: res.location.lat,
+                longitude: res.location.lon,
+                name: res.location.name,
+                admin1: res.location.region.filter(|region| !region.is_empty()),
+                country_code: None,
+                timezone: res.location.tz_id,
+            }),
+        }
+    }
+}
+
+impl WeatherProvider for WeatherApiCom {
+    fn name(&self) -> &'static str {
+        "WeatherAPI.com"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config.get(&Self::weather_url(config)?)?;
+
+        if let Err(err) = res.error_for_status_ref() {
+            return Err(Self::error(config, err, &res.text().unwrap_or_default()));
+        }
+
+        Ok(Self::weather_data(res.json()?, config))
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        Self::weather_url(config)
+    }
+
+    fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let res = config.get(&Self::alerts_url(config)?)?;
+
+        if let Err(err) = res.error_for_status_ref() {
+            return Err(Self::error(config, err, &res.text().unwrap_or_default()));
+        }
+
+        Ok(Self::alerts(res.json()?))
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for WeatherApiCom {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config.get_async(&Self::weather_url(config)?).await?;
+
+        if let Err(err) = res.error_for_status_ref() {
+            return Err(Self::error(
+                config,
+                err,
+                &res.text().await.unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self::weather_data(res.json().await?, config))
+    }
+
+    async fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let res = config.get_async(&Self::alerts_url(config)?).await?;
+
+        if let Err(err) = res.error_for_status_ref() {
+            return Err(Self::error(
+                config,
+                err,
+                &res.text().await.unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self::alerts(res.json().await?))
+    }
+}
+
+impl NationalWeatherService {
+    /// Looks up the forecast office and grid cell for a point. The API only
+    /// covers the United States and answers 404 anywhere else.
+    fn points_url(latitude: f32, longitude: f32) -> String {
+        format!(
+            "https://api.weather.gov/points/{:.4},{:.4}",
+            latitude, longitude
+        )
+    }
+
+    /// Requested in SI units, so temperatures are in °C and wind in km/h.
+    fn hourly_url(point: &nws::Point) -> String {
+        format!("{}?units=si", point.properties.forecast_hourly)
+    }
+
+    /// Maps the free-text short forecast, e.g. `Chance Light Rain` or
+    /// `Mostly Sunny`, to a condition.
+    fn condition(short_forecast: &str) -> WeatherCondition {
+        use crate::WeatherCondition::*;
+        let forecast = short_forecast.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| forecast.contains(word));
+
+        if mentions(&["thunder"]) {
+            Thunderstorms
+        } else if mentions(&["snow showers", "flurries"]) {
+            SnowShowers
+        } else if mentions(&["ice pellets", "hail"]) {
+            SnowGrains
+        } else if mentions(&["snow", "sleet", "blizzard"]) {
+            Snowy
+        } else if mentions(&["drizzle"]) {
+            Drizzle
+        } else if mentions(&["showers"]) {
+            RainShowers
+        } else if mentions(&["rain"]) {
+            Rainy
+        } else if mentions(&["fog", "haze", "smoke"]) {
+            Foggy
+        } else if mentions(&["partly", "mostly sunny", "mostly clear"]) {
+            PartlyCloudy
+        } else if mentions(&["cloudy", "overcast"]) {
+            Overcast
+        } else if mentions(&["sunny", "clear", "fair"]) {
+            Clear
+        } else {
+            Unknown
+        }
+    }
+
+    fn alerts_url(latitude: f32, longitude: f32) -> String {
+        format!(
+            "https://api.weather.gov/alerts/active?point={:.4},{:.4}",
+            latitude, longitude
+        )
+    }
+
+    fn alerts(res: nws::Alerts) -> Vec<WeatherAlert> {
+        res.features
+            .into_iter()
+            .map(|feature| WeatherAlert {
+                event: feature.properties.event,
+                headline: feature.properties.headline,
+            })
+            .collect()
+    }
+
+    fn not_found(latitude: f32, longitude: f32) -> ProviderError {
+        ProviderError::LocationNotFound(format!("{}, {} outside the US", latitude, longitude))
+    }
+
+    /// Reads the current hour from the hourly forecast. The wind speed may be a
+    /// range such as `10 to 15 km/h`, of which the lower bound is used.
+    fn weather_data(
+        point: nws::Point,
+        forecast: nws::Forecast,
+        location: Option<ResolvedLocation>,
+        (latitude, longitude): (f32, f32),
+        config: &Config,
+    ) -> Result<WeatherData, ProviderError> {
+        let now = forecast
+            .properties
+            .periods
+            .into_iter()
+            .next()
+            .ok_or_else(|| Self::not_found(latitude, longitude))?;
+        let humidity = now.relative_humidity.and_then(|humidity| humidity.value);
+        let wind_kph = now
+            .wind_speed
+            .split_whitespace()
+            .next()
+            .and_then(|speed| speed.parse::<f32>().ok())
+            .unwrap_or_default();
+        let wind_speed = format_wind_speed(wind_kph, config);
+        let wind_degrees = direction_to_degree(&now.wind_direction).unwrap_or_default();
+        let feels_like = apparent_temperature(
+            now.temperature,
+            humidity.unwrap_or_default(),
+            wind_kph / 3.6,
+        );
+        let location = location.or_else(|| {
+            let relative = point.properties.relative_location?.properties;
+
+            Some(ResolvedLocation {
+                latitude,
+                longitude,
+                name: relative.city,
+                admin1: relative.state,
+                country_code: Some("US".to_string()),
+                timezone: point.properties.time_zone,
+            })
+        });
+
+        Ok(WeatherData {
+            temperature: format_temperature(now.temperature, Celsius, config),
+            feels_like: format_feels_like(
+                feels_like,
+                Celsius,
+                (now.temperature, humidity, wind_kph),
+                config,
+            ),
+            wind_speed,
+            wind_gust: None,
+            wind_direction: degree_to_direction(wind_degrees, config.wind_direction_points),
+            wind_degrees,
+            humidity: humidity.map(|humidity| humidity.round() as u8),
+            dew_point: humidity
+                .and_then(|humidity| format_dew_point(now.temperature, humidity, config)),
+            pressure: None,
+            visibility: None,
+            cloud_cover: None,
+            uv_index: None,
+            precipitation: None,
+            precipitation_probability: now
+                .probability_of_precipitation
+                .and_then(|probability| probability.value)
+                .map(|probability| probability.round() as u8),
+            snowfall: None,
+            snow_depth: None,
+            sunrise: None,
+            sunset: None,
+            utc_offset: None,
+            is_day: now.is_daytime,
+            condition: Self::condition(&now.short_forecast),
+            condition_code: None,
+            location,
+        })
+    }
+}
+
+impl WeatherProvider for NationalWeatherService {
+    fn name(&self) -> &'static str {
+        "National Weather Service"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = OpenMeteo.coordinates(config)?;
+
+        let res = config.get(&Self::points_url(latitude, longitude))?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Self::not_found(latitude, longitude));
+        }
+        let point = res.error_for_status()?.json()?;
+
+        let forecast = config
+            .get(&Self::hourly_url(&point))?
+            .error_for_status()?
+            .json()?;
+
+        Self::weather_data(point, forecast, location, (latitude, longitude), config)
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo.coordinates(config)?;
+
+        Ok(Self::points_url(latitude, longitude))
+    }
+
+    fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo.coordinates(config)?;
+
+        let res = config
+            .get(&Self::alerts_url(latitude, longitude))?
+            .error_for_status()?
+            .json()?;
+
+        Ok(Self::alerts(res))
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for NationalWeatherService {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let ((latitude, longitude), location) = OpenMeteo::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::points_url(latitude, longitude))
+            .await?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Self::not_found(latitude, longitude));
+        }
+        let point = res.error_for_status()?.json().await?;
+
+        let forecast = config
+            .get_async(&Self::hourly_url(&point))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Self::weather_data(point, forecast, location, (latitude, longitude), config)
+    }
+
+    async fn fetch_alerts(&self, config: &Config) -> Result<Vec<WeatherAlert>, ProviderError> {
+        let ((latitude, longitude), _) = OpenMeteo::coordinates_async(config).await?;
+
+        let res = config
+            .get_async(&Self::alerts_url(latitude, longitude))
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Self::alerts(res))
+    }
+}
+
+/// Formats a temperature given in `unit` in the configured `temperature_unit`.
+/// Kelvin is never written with a degree sign. The value is rounded to
+/// `temperature_precision` decimals.
+fn format_temperature(value: f32, unit: ConfigTemperatureUnit, config: &Config) -> String {
+    let target = config.temperature_unit();
+    let letter = target.letter();
+    let value = if unit == target {
+        value
+    } else {
+        target.from_celsius(unit.to_celsius(value))
+    };
+    let precision = usize::from(config.temperature_precision);
+    let factor = 10f32.powi(i32::from(config.temperature_precision));
+    // Adding zero turns -0 into 0.
+    let value = format!("{:.*}", precision, (value * factor).round() / factor + 0.0);
+
+    match config.temperature_suffix_style {
+        ConfigTemperatureSuffixStyle::Spaced if target == Kelvin => {
+            format!("{} {}", value, letter)
+        }
+        _ if target == Kelvin => format!("{}{}", value, letter),
+        ConfigTemperatureSuffixStyle::DegreeLetter => format!("{}°{}", value, letter),
+        ConfigTemperatureSuffixStyle::DegreeOnly => format!("{}°", value),
+        ConfigTemperatureSuffixStyle::LetterOnly => format!("{}{}", value, letter),
+        ConfigTemperatureSuffixStyle::Spaced => format!("{} °{}", value, letter),
+    }
+}
+
+impl Wttr {
+    fn weather_url(config: &Config) -> Result<String, ProviderError> {
+        let location = match location(config)? {
+            ConfigLocation::Coordinates(lat, lon) => format!("{},{}", lat, lon),
+            ConfigLocation::City(city, country) | ConfigLocation::PostalCode(city, country) => {
+                format!("{},{}", city, country)
+            }
+        };
+
+        Ok(format!("https://wttr.in/{}?format=j1", location))
+    }
+
+    /// Maps a WorldWeatherOnline weather code, which wttr.in passes through,
+    /// to a condition.
+    fn condition(code: i32) -> WeatherCondition {
+        use crate::WeatherCondition::*;
+        match code {
+            113 => Clear,
+            116 => PartlyCloudy,
+            119 | 122 => Overcast,
+            143 | 248 | 260 => Foggy,
+            185 | 263 | 266 | 281 | 284 => Drizzle,
+            176 | 293..=314 => Rainy,
+            179 | 182 | 227 | 230 | 317..=338 => Snowy,
+            350 | 374 | 377 => SnowGrains,
+            353..=359 => RainShowers,
+            362..=371 => SnowShowers,
+            200 | 386..=395 => Thunderstorms,
+            _ => Unknown,
+        }
+    }
+
+    fn weather_data(res: wttr::Root, config: &Config) -> Result<WeatherData, ProviderError> {
+        let Some(current) = res.current_condition.into_iter().next() else {
+            return Err(ProviderError::LocationNotFound(
+                location(config)?.to_string(),
+            ));
+        };
+        let number = |value: &str| value.trim().parse::<f32>().unwrap_or_default();
+        let wind_speed = format_wind_speed(number(&current.windspeed_kmph), config);
+        let wind_degrees = number(&current.winddir_degree) as i16;
+        let code = current.weather_code.trim().parse().ok();
+        let location = res
+            .nearest_area
+            .into_iter()
+            .next()
+            .map(|area| ResolvedLocation {
+                latitude: number(&area.latitude),
+                longitude: number(&area.longitude),
+                name: area
+                    .area_name
+                    .into_iter()
+                    .next()
+                    .map(|name| name.value)
+                    .unwrap_or_default(),
+                admin1: area.region.into_iter().next().map(|region| region.value),
+                country_code: None,
+                timezone: None,
+            });
+
+        Ok(WeatherData {
+            temperature: format_temperature(number(&current.temp_c), Celsius, config),
+            feels_like: format_feels_like(
+                number(&current.feels_like_c),
+                Celsius,
+                (
+                    number(&current.temp_c),
+                    current.humidity.as_deref().map(number),
+                    number(&current.windspeed_kmph),
+                ),
+                config,
+            ),
+            wind_speed,
+            wind_gust: None,
+            wind_direction: degree_to_direction(wind_degrees, config.wind_direction_points),
+            wind_degrees,
+            humidity: current
+                .humidity
+                .as_ref()
+                .and_then(|humidity| humidity.trim().parse().ok()),
+            dew_point: current
+                .humidity
+                .as_ref()
+                .and_then(|humidity| humidity.trim().parse().ok())
+                .and_then(|humidity| format_dew_point(number(&current.temp_c), humidity, config)),
+            pressure: current
+                .pressure
+                .and_then(|pressure| pressure.trim().parse().ok()),
+            visibility: current
+                .visibility
+                .and_then(|km| km.trim().parse::<f32>().ok())
+                .map(|km| km * 1000.0),
+            cloud_cover: current
+                .cloudcover
+                .and_then(|cover| cover.trim().parse().ok()),
+            uv_index: current
+                .uv_index
+                .and_then(|uv_index| uv_index.trim().parse().ok()),
+            precipitation: current
+                .precip_mm
+                .and_then(|precipitation| precipitation.trim().parse().ok()),
+            precipitation_probability: None,
+            snowfall: None,
+            snow_depth: None,
+            sunrise: None,
+            sunset: None,
+            utc_offset: None,
+            is_day: None,
+            condition: code.map_or(WeatherCondition::Unknown, Self::condition),
+            condition_code: code,
+            location,
         })
     }
 }
 
-fn degree_to_direction(degree: i16) -> String {
-    match degree {
-        0..=22 => "N",
-        23..=67 => "NE",
-        68..=112 => "E",
-        113..=157 => "SE",
-        158..=202 => "S",
-        203..=247 => "SW",
-        248..=292 => "W",
-        293..=337 => "NW",
-        _ => "N",
+impl WeatherProvider for Wttr {
+    fn name(&self) -> &'static str {
+        "wttr.in"
+    }
+
+    fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config
+            .get(&Self::weather_url(config)?)?
+            .error_for_status()?
+            .json()?;
+
+        Self::weather_data(res, config)
+    }
+
+    fn build_url(&self, config: &Config) -> Result<String, ProviderError> {
+        Self::weather_url(config)
+    }
+}
+
+#[cfg(feature = "async")]
+impl WeatherProviderAsync for Wttr {
+    async fn fetch_weather(&self, config: &Config) -> Result<WeatherData, ProviderError> {
+        let res = config
+            .get_async(&Self::weather_url(config)?)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Self::weather_data(res, config)
+    }
+}
+
+/// Converts a speed in km/h to the configured `wind_unit` and formats it.
+fn format_wind_speed(kmh: f32, config: &Config) -> String {
+    let unit = config.wind_unit();
+
+    unit.format(unit.from_kmh(kmh))
+}
+
+/// The location to query, which [`Config::resolve_location`] fills in.
+fn location(config: &Config) -> Result<&ConfigLocation, ProviderError> {
+    config.location.as_ref().ok_or(ProviderError::NoLocation)
+}
+
+/// The provider's feels-like temperature, given in `unit`, or with
+/// `feels_like = "computed"` the [`apparent::feels_like`] of the reading.
+fn format_feels_like(
+    reported: f32,
+    unit: ConfigTemperatureUnit,
+    (celsius, humidity, kmh): (f32, Option<f32>, f32),
+    config: &Config,
+) -> String {
+    match config.feels_like {
+        ConfigFeelsLike::Provider => format_temperature(reported, unit, config),
+        ConfigFeelsLike::Computed => format_temperature(
+            apparent::feels_like(celsius, humidity, kmh),
+            Celsius,
+            config,
+        ),
+    }
+}
+
+/// Derives a feels-like temperature for providers without one, using the
+/// Australian apparent temperature formula like Open-Meteo does. The wind
+/// speed is in m/s.
+fn apparent_temperature(celsius: f32, humidity: f32, wind_speed: f32) -> f32 {
+    let vapour_pressure = humidity / 100.0 * 6.105 * (17.27 * celsius / (237.7 + celsius)).exp();
+
+    celsius + 0.33 * vapour_pressure - 0.7 * wind_speed - 4.0
+}
+
+/// Derives the dew point for providers without one from the temperature and
+/// relative humidity, using the Magnus formula with Sonntag's constants.
+fn dew_point(celsius: f32, humidity: f32) -> f32 {
+    let gamma = (humidity / 100.0).ln() + 17.62 * celsius / (243.12 + celsius);
+
+    243.12 * gamma / (17.62 - gamma)
+}
+
+/// The [`dew_point`], formatted like a temperature. There is none for a
+/// humidity of 0%.
+fn format_dew_point(celsius: f32, humidity: f32, config: &Config) -> Option<String> {
+    (humidity > 0.0).then(|| format_temperature(dew_point(celsius, humidity), Celsius, config))
+}
+
+const DIRECTIONS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+fn degree_to_direction(degree: i16, points: ConfigCompassPoints) -> String {
+    let points = u8::from(points) as usize;
+    let sector = 360.0 / points as f32;
+    let index = (degree.rem_euclid(360) as f32 / sector).round() as usize % points;
+
+    DIRECTIONS[index * (DIRECTIONS.len() / points)].to_string()
+}
+
+/// The inverse of [`degree_to_direction`] for providers that report a compass
+/// point such as `SW` instead of degrees.
+fn direction_to_degree(direction: &str) -> Option<i16> {
+    DIRECTIONS
+        .iter()
+        .position(|&point| point == direction)
+        .map(|index| (index as f32 * 22.5).round() as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigUnits;
+
+    #[test]
+    fn dew_point_magnus_reference_values() {
+        let cases = [
+            (20.0, 50.0, 9.3),
+            (30.0, 80.0, 26.2),
+            (25.0, 100.0, 25.0),
+            (0.0, 100.0, 0.0),
+            (-10.0, 60.0, -16.3),
+        ];
+
+        for (celsius, humidity, expected) in cases {
+            let dew_point = dew_point(celsius, humidity);
+
+            assert!(
+                (dew_point - expected).abs() < 0.1,
+                "{}°C at {}%: {}",
+                celsius,
+                humidity,
+                dew_point
+            );
+        }
+    }
+
+    #[test]
+    fn no_dew_point_without_humidity() {
+        let config = Config::default();
+
+        assert_eq!(format_dew_point(20.0, 0.0, &config), None);
+        assert_eq!(
+            format_dew_point(20.0, 50.0, &config).as_deref(),
+            Some("9°C")
+        );
+    }
+
+    /// The Australian apparent temperature, with the wind in m/s.
+    #[test]
+    fn apparent_temperature_reference_values() {
+        let cases = [
+            (25.0, 50.0, 2.0, 24.8),
+            (30.0, 70.0, 0.0, 35.8),
+            (5.0, 80.0, 5.0, -0.2),
+        ];
+
+        for (celsius, humidity, wind, expected) in cases {
+            let apparent = apparent_temperature(celsius, humidity, wind);
+
+            assert!(
+                (apparent - expected).abs() < 0.1,
+                "{}°C: {}",
+                celsius,
+                apparent
+            );
+        }
+    }
+
+    #[test]
+    fn temperature_suffix_styles() {
+        use ConfigTemperatureSuffixStyle::*;
+
+        let cases = [
+            (DegreeLetter, "20°C", "293K"),
+            (DegreeOnly, "20°", "293K"),
+            (LetterOnly, "20C", "293K"),
+            (Spaced, "20 °C", "293 K"),
+        ];
+
+        for (style, celsius, kelvin) in cases {
+            let mut config = Config {
+                temperature_suffix_style: style,
+                ..Config::default()
+            };
+
+            assert_eq!(format_temperature(20.4, Celsius, &config), celsius);
+
+            config.temperature_unit = Some(Kelvin);
+            assert_eq!(format_temperature(20.0, Celsius, &config), kelvin);
+        }
+    }
+
+    #[test]
+    fn temperature_conversion_and_rounding() {
+        let mut config = Config::default();
+
+        assert_eq!(format_temperature(-0.4, Celsius, &config), "0°C");
+        assert_eq!(
+            format_temperature(68.0, ConfigTemperatureUnit::Fahrenheit, &config),
+            "20°C"
+        );
+
+        config.temperature_precision = 1;
+        config.temperature_unit = Some(ConfigTemperatureUnit::Fahrenheit);
+        assert_eq!(format_temperature(21.0, Celsius, &config), "69.8°F");
+    }
+
+    fn open_weather_map(speed: f32, gust: f32) -> open_weather_map::Root {
+        serde_json::from_value(serde_json::json!({
+            "main": { "temp": 20.0, "feels_like": 19.0 },
+            "weather": [{ "id": 800, "main": "Clear", "description": "clear sky", "icon": "01d" }],
+            "wind": { "deg": 90, "speed": speed, "gust": gust },
+        }))
+        .unwrap()
+    }
+
+    /// OpenWeatherMap reports m/s for `units=metric` and `standard`, mph for
+    /// `imperial`, and the request picks those by the temperature unit.
+    #[test]
+    fn open_weather_map_wind_conversion() {
+        let cases = [
+            (ConfigUnits::Metric, None, None, "36.0km/h", "54.0km/h"),
+            (
+                ConfigUnits::Metric,
+                None,
+                Some(ConfigWindUnit::Knots),
+                "19.4kn",
+                "29.2kn",
+            ),
+            (
+                ConfigUnits::Metric,
+                None,
+                Some(ConfigWindUnit::Ms),
+                "10.0m/s",
+                "15.0m/s",
+            ),
+            (
+                ConfigUnits::Metric,
+                Some(Kelvin),
+                None,
+                "36.0km/h",
+                "54.0km/h",
+            ),
+            (ConfigUnits::Imperial, None, None, "10.0mph", "15.0mph"),
+            (
+                ConfigUnits::Imperial,
+                None,
+                Some(ConfigWindUnit::Kmh),
+                "16.1km/h",
+                "24.1km/h",
+            ),
+            (
+                ConfigUnits::Imperial,
+                None,
+                Some(ConfigWindUnit::Ms),
+                "4.5m/s",
+                "6.7m/s",
+            ),
+            (
+                ConfigUnits::Imperial,
+                Some(Celsius),
+                None,
+                "22.4mph",
+                "33.6mph",
+            ),
+        ];
+
+        for (units, temperature_unit, wind_unit, speed, gust) in cases {
+            let config = Config {
+                units,
+                temperature_unit,
+                wind_unit,
+                ..Config::default()
+            };
+            let weather = OpenWeatherMap::weather_data(open_weather_map(10.0, 15.0), &config);
+
+            assert_eq!(weather.wind_speed, speed);
+            assert_eq!(weather.wind_gust.as_deref(), Some(gust));
+        }
+    }
+
+    #[test]
+    fn compass_boundaries() {
+        let cases = [
+            (0, "N"),
+            (11, "N"),
+            (12, "NNE"),
+            (22, "NNE"),
+            (34, "NE"),
+            (90, "E"),
+            (326, "NW"),
+            (337, "NNW"),
+            (348, "NNW"),
+            (349, "N"),
+            (360, "N"),
+            (-11, "N"),
+        ];
+
+        for (degree, direction) in cases {
+            assert_eq!(
+                degree_to_direction(degree, ConfigCompassPoints::Sixteen),
+                direction,
+                "{}°",
+                degree
+            );
+        }
+    }
+
+    #[test]
+    fn compass_resolutions() {
+        use ConfigCompassPoints::*;
+
+        let cases = [
+            (0, "N", "N", "N"),
+            (30, "N", "NE", "NNE"),
+            (44, "N", "NE", "NE"),
+            (45, "E", "NE", "NE"),
+            (100, "E", "E", "E"),
+            (200, "S", "S", "SSW"),
+            (250, "W", "W", "WSW"),
+            (314, "W", "NW", "NW"),
+            (315, "N", "NW", "NW"),
+            (338, "N", "N", "NNW"),
+        ];
+
+        for (degree, four, eight, sixteen) in cases {
+            assert_eq!(degree_to_direction(degree, Four), four, "{}° of 4", degree);
+            assert_eq!(
+                degree_to_direction(degree, Eight),
+                eight,
+                "{}° of 8",
+                degree
+            );
+            assert_eq!(
+                degree_to_direction(degree, Sixteen),
+                sixteen,
+                "{}° of 16",
+                degree
+            );
+        }
+    }
+
+    #[test]
+    fn direction_round_trips_through_degrees() {
+        for direction in DIRECTIONS {
+            let degree = direction_to_degree(direction).unwrap();
+
+            assert_eq!(
+                degree_to_direction(degree, ConfigCompassPoints::Sixteen),
+                direction
+            );
+        }
+        assert_eq!(direction_to_degree("X"), None);
     }
-    .to_string()
 }
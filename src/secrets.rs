@@ -0,0 +1,40 @@
+//! API keys stored in the operating system's keyring, referenced from the
+//! config as `api_key_keyring = "service/account"`.
+
+fn split(reference: &str) -> Result<(&str, &str), String> {
+    reference
+        .split_once('/')
+        .ok_or_else(|| format!("'{}' is not of the form 'service/account'", reference))
+}
+
+#[cfg(feature = "keyring")]
+pub fn get(reference: &str) -> Result<String, String> {
+    let (service, account) = split(reference)?;
+
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.get_password())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "keyring")]
+pub fn set(reference: &str, key: &str) -> Result<(), String> {
+    let (service, account) = split(reference)?;
+
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.set_password(key))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn get(reference: &str) -> Result<String, String> {
+    split(reference)?;
+
+    Err("built without the `keyring` feature".to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn set(reference: &str, _key: &str) -> Result<(), String> {
+    split(reference)?;
+
+    Err("built without the `keyring` feature".to_string())
+}
@@ -0,0 +1,126 @@
+//! Human-friendly time specifications such as `"in 3 hours"` or
+//! `"tomorrow morning"`, resolved against the current local time.
+
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+
+const SUPPORTED: &str = "now, in <n> minutes|hours|days, HH:MM, noon, midnight, \
+     [this|tomorrow] morning|afternoon|evening, tonight, tomorrow [night|noon]";
+
+fn time_of_day(word: &str) -> Option<NaiveTime> {
+    let hour = match word {
+        "morning" => 8,
+        "noon" => 12,
+        "afternoon" => 15,
+        "evening" => 19,
+        "night" | "tonight" => 22,
+        _ => return None,
+    };
+
+    NaiveTime::from_hms_opt(hour, 0, 0)
+}
+
+fn offset(amount: &str, unit: &str) -> Option<Duration> {
+    let amount = match amount {
+        "a" | "an" => 1,
+        amount => amount.parse().ok()?,
+    };
+
+    match unit.trim_end_matches('s') {
+        "minute" | "min" => Duration::try_minutes(amount),
+        "hour" | "h" => Duration::try_hours(amount),
+        "day" | "d" => Duration::try_days(amount),
+        _ => None,
+    }
+}
+
+/// Resolves `spec` to a concrete point in time relative to `now`.
+pub fn resolve(spec: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let spec = spec.trim().to_lowercase();
+    let words: Vec<&str> = spec.split_whitespace().collect();
+    let today = now.date();
+    // Only `None` on the last day chrono can represent.
+    let tomorrow = today.succ_opt();
+
+    let resolved = match words.as_slice() {
+        ["now"] => Some(now),
+        ["in", amount, unit] => {
+            offset(amount, unit).and_then(|offset| now.checked_add_signed(offset))
+        }
+        ["midnight"] => tomorrow.map(|tomorrow| tomorrow.and_time(NaiveTime::MIN)),
+        ["tomorrow"] => now.checked_add_signed(Duration::days(1)),
+        ["tomorrow", word] => time_of_day(word)
+            .zip(tomorrow)
+            .map(|(time, tomorrow)| tomorrow.and_time(time)),
+        ["this", word] | [word] => time_of_day(word)
+            .or_else(|| NaiveTime::parse_from_str(word, "%H:%M").ok())
+            .map(|time| today.and_time(time)),
+        _ => None,
+    };
+
+    resolved.ok_or_else(|| format!("Unrecognized time '{}'. Supported: {}", spec, SUPPORTED))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn resolves_each_phrase() {
+        let now = at(14, 10, 30);
+        let cases = [
+            ("now", at(14, 10, 30)),
+            ("in 3 hours", at(14, 13, 30)),
+            ("in an hour", at(14, 11, 30)),
+            ("in 45 minutes", at(14, 11, 15)),
+            ("in 2 days", at(16, 10, 30)),
+            ("in 90 min", at(14, 12, 0)),
+            ("14:15", at(14, 14, 15)),
+            ("noon", at(14, 12, 0)),
+            ("midnight", at(15, 0, 0)),
+            ("morning", at(14, 8, 0)),
+            ("this afternoon", at(14, 15, 0)),
+            ("this evening", at(14, 19, 0)),
+            ("tonight", at(14, 22, 0)),
+            ("tomorrow", at(15, 10, 30)),
+            ("tomorrow morning", at(15, 8, 0)),
+            ("tomorrow noon", at(15, 12, 0)),
+            ("Tomorrow  Night", at(15, 22, 0)),
+        ];
+
+        for (spec, expected) in cases {
+            assert_eq!(resolve(spec, now), Ok(expected), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_phrases() {
+        for spec in [
+            "",
+            "later",
+            "in 3 weeks",
+            "in many hours",
+            "25:00",
+            "next morning",
+        ] {
+            let err = resolve(spec, at(14, 10, 30)).unwrap_err();
+
+            assert!(err.contains(SUPPORTED), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_out_of_range() {
+        assert!(resolve("in 9999999999999999 days", at(14, 10, 30)).is_err());
+        assert!(resolve("in 99999999999 days", at(14, 10, 30)).is_err());
+        assert!(resolve("tomorrow", NaiveDateTime::MAX).is_err());
+        assert!(resolve("midnight", NaiveDateTime::MAX).is_err());
+    }
+}